@@ -0,0 +1,127 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering};
+
+// Install in a day's binary with
+//
+//     #[global_allocator]
+//     static ALLOC: common::alloc::CountingAllocator = common::alloc::CountingAllocator;
+//
+// Nothing is counted until `enable` is called (the runner does this for `--alloc`), and live
+// bytes are measured from that point on.
+pub struct CountingAllocator;
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+
+fn record_alloc(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED.fetch_add(size, Ordering::Relaxed);
+    let live = LIVE.fetch_add(size as isize, Ordering::Relaxed) + size as isize;
+    PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+fn record_dealloc(size: usize) {
+    LIVE.fetch_sub(size as isize, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() && COUNTING.load(Ordering::Relaxed) {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if COUNTING.load(Ordering::Relaxed) {
+            record_dealloc(layout.size());
+        }
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() && COUNTING.load(Ordering::Relaxed) {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() && COUNTING.load(Ordering::Relaxed) {
+            record_dealloc(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+pub fn enable(on: bool) {
+    if on && !COUNTING.load(Ordering::Relaxed) {
+        LIVE.store(0, Ordering::Relaxed);
+        PEAK.store(0, Ordering::Relaxed);
+    }
+    COUNTING.store(on, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    COUNTING.load(Ordering::Relaxed)
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct AllocStats {
+    pub allocations: usize,
+    pub bytes: usize,
+    pub peak_live: usize,
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "allocs: {}, bytes: {}, peak: {}", self.allocations, self.bytes, self.peak_live)
+    }
+}
+
+// Accumulates the allocations made between each begin/end pair. Phases that are interleaved
+// (parse and process in the sequential runner) use one meter each and begin/end per item.
+#[derive(Default)]
+pub struct PhaseMeter {
+    stats: AllocStats,
+    start_allocations: usize,
+    start_bytes: usize,
+}
+
+impl PhaseMeter {
+    pub fn begin(&mut self) {
+        if !is_enabled() {
+            return;
+        }
+        self.start_allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        self.start_bytes = ALLOCATED.load(Ordering::Relaxed);
+        PEAK.store(LIVE.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    pub fn end(&mut self) {
+        if !is_enabled() {
+            return;
+        }
+        self.stats.allocations += ALLOCATIONS.load(Ordering::Relaxed) - self.start_allocations;
+        self.stats.bytes += ALLOCATED.load(Ordering::Relaxed) - self.start_bytes;
+        let peak = PEAK.load(Ordering::Relaxed).max(0) as usize;
+        self.stats.peak_live = self.stats.peak_live.max(peak);
+    }
+
+    pub fn stats(&self) -> Option<AllocStats> {
+        if is_enabled() {
+            Some(self.stats)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,14 @@
+pub fn flag(name: &str) -> bool {
+    std::env::args().any(|a| a == name)
+}
+
+pub fn value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|a| a != name);
+    args.next();
+    args.next()
+}
+
+// First argument that isn't a flag, e.g. `lint` in `cargo run -- lint`
+pub fn command() -> Option<String> {
+    std::env::args().nth(1).filter(|a| !a.starts_with("--"))
+}
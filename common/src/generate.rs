@@ -0,0 +1,108 @@
+use std::fmt;
+
+// SplitMix64: tiny, fast and stable across platforms and releases, so a seed always
+// produces the same input.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Empty range");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // low..=high
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        assert!(low <= high, "Empty range");
+        let span = (high as i128 - low as i128 + 1) as u128;
+        (low as i128 + (self.next_u64() as u128 % span) as i128) as i64
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 <= probability
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+pub type Generator = fn(&mut Rng, usize) -> String;
+
+pub fn generate(generator: Generator, seed: u64, size: usize) -> String {
+    generator(&mut Rng::new(seed), size)
+}
+
+// `generate` command for a day's binary: writes an input to stdout, sized and seeded by
+// `--size` and `--seed`
+pub fn run(generator: Generator, default_size: usize) {
+    let seed = crate::args::value("--seed").map_or(1, |s| s.parse().expect("Bad --seed"));
+    let size = crate::args::value("--size").map_or(default_size, |s| s.parse().expect("Bad --size"));
+    print!("{}", generate(generator, seed, size));
+}
+
+// Property check that `parse(print(x)) == x` for `cases` values drawn by `arbitrary`. The seed
+// is fixed so a failure always reproduces.
+pub fn assert_round_trip<T, A, P, R>(cases: usize, arbitrary: A, print: P, parse: R)
+    where T: PartialEq + fmt::Debug, A: Fn(&mut Rng) -> T, P: Fn(&T) -> String, R: Fn(&str) -> T {
+    let mut rng = Rng::new(0x5eed);
+    for case in 0..cases {
+        let value = arbitrary(&mut rng);
+        let printed = print(&value);
+        let parsed = parse(&printed);
+        assert_eq!(value, parsed, "case {}: {:?} printed as {:?}", case, value, printed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(rng: &mut Rng, size: usize) -> String {
+        (0..size).map(|_| format!("{}\n", rng.range(-5, 5))).collect()
+    }
+
+    #[test]
+    fn test_deterministic() {
+        assert_eq!(generate(numbers, 42, 100), generate(numbers, 42, 100));
+        assert_ne!(generate(numbers, 42, 100), generate(numbers, 43, 100));
+
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            assert!((-5..=5).contains(&rng.range(-5, 5)));
+            assert!(rng.below(3) < 3);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_round_trip(100, |rng| rng.range(i64::MIN + 1, i64::MAX), |n| n.to_string(), |s| s.parse().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "printed as")]
+    fn test_round_trip_mismatch() {
+        assert_round_trip(100, |rng| rng.range(-10, 10), |n| n.abs().to_string(), |s| s.parse().unwrap());
+    }
+}
@@ -0,0 +1,344 @@
+use std::any;
+use std::fs::{self, OpenOptions};
+use std::cell::Cell;
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+use std::process::Command;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::{args, fmt_dur, RunReport};
+
+// Every `run` of a day's binary is appended to `history.tsv` in the working directory, one
+// tab separated line per part: unix time, day, part, puzzle, git commit, input hash, run
+// time in microseconds, the result and the options it ran with. `--no-history` skips it.
+pub const FILE: &str = "history.tsv";
+
+// A run is flagged when it's this many percent slower than the median of the last `WINDOW`
+// runs of the same part on the same input, with the same options and answer. `--regression-margin PCT` changes it.
+pub const DEFAULT_MARGIN: f64 = 25.0;
+pub const WINDOW: usize = 10;
+const MIN_SAMPLES: usize = 3;
+
+// Arguments that don't change what's solved, left out of a run's options. The input is
+// already told apart by its hash.
+const NOT_SOLVING_FLAGS: [&str; 2] = ["--no-history", "--alloc"];
+const NOT_SOLVING_VALUES: [&str; 4] = ["--input", "--regression-margin", "--trace", "--snapshot"];
+
+// Parts are numbered in the order a binary runs them
+static PART: AtomicUsize = AtomicUsize::new(0);
+
+// Numbers the part a binary is about to run, for its history and snapshots
+pub fn next_part() -> usize {
+    PART.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub time: u64,
+    pub day: String,
+    pub part: usize,
+    pub puzzle: String,
+    pub commit: String,
+    pub input_hash: String,
+    pub run: Duration,
+    pub result: String,
+    // e.g. `--k 3 --precision big`, empty for a default run
+    pub options: String,
+}
+
+impl Entry {
+    fn to_line(&self) -> String {
+        // Tabs and newlines would break the line up, and results never need them
+        let result = self.result.replace(['\t', '\n'], " ");
+        format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n", self.time, self.day, self.part, self.puzzle, self.commit,
+                self.input_hash, self.run.as_micros(), result, self.options)
+    }
+
+    // Lines from before options were recorded have 8 fields, and ran with the defaults
+    pub fn from_line(line: &str) -> Option<Entry> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 8 && fields.len() != 9 {
+            return None;
+        }
+        Some(Entry {
+            time: fields[0].parse().ok()?,
+            day: fields[1].to_string(),
+            part: fields[2].parse().ok()?,
+            puzzle: fields[3].to_string(),
+            commit: fields[4].to_string(),
+            input_hash: fields[5].to_string(),
+            run: Duration::from_micros(fields[6].parse().ok()?),
+            result: fields[7].to_string(),
+            options: fields.get(8).map_or(String::new(), |o| o.to_string()),
+        })
+    }
+
+    // Parts are only numbered by the order they run in, so the puzzle, its options and its
+    // answer are compared too
+    pub fn same_run(&self, other: &Entry) -> bool {
+        self.day == other.day && self.part == other.part && self.puzzle == other.puzzle
+            && self.options == other.options && self.input_hash == other.input_hash && self.result == other.result
+    }
+}
+
+pub struct Comparison {
+    pub median: Duration,
+    pub samples: usize,
+    // How much slower than the median, negative when faster
+    pub percent: f64,
+}
+
+// Compares `entry` with the runs of the same part on the same input before it in `history`,
+// or None if there aren't enough of them yet
+pub fn compare(history: &[Entry], entry: &Entry) -> Option<Comparison> {
+    let mut previous: Vec<Duration> = history.iter()
+        .filter(|e| e.same_run(entry))
+        .map(|e| e.run)
+        .collect();
+    previous = previous.split_off(previous.len().saturating_sub(WINDOW));
+    if previous.len() < MIN_SAMPLES {
+        return None;
+    }
+
+    let median = median(&mut previous);
+    let percent = (entry.run.as_secs_f64() / median.as_secs_f64() - 1.0) * 100.0;
+    Some(Comparison { median, samples: previous.len(), percent })
+}
+
+pub fn regression(history: &[Entry], entry: &Entry, margin: f64) -> Option<Comparison> {
+    compare(history, entry).filter(|c| c.percent > margin)
+}
+
+pub fn median(durations: &mut [Duration]) -> Duration {
+    durations.sort();
+    let middle = durations.len() / 2;
+    if durations.len().is_multiple_of(2) {
+        (durations[middle - 1] + durations[middle]) / 2
+    } else {
+        durations[middle]
+    }
+}
+
+pub fn margin() -> f64 {
+    args::value("--regression-margin").map_or(DEFAULT_MARGIN, |m| m.parse().expect("Bad --regression-margin"))
+}
+
+// Missing history is empty, and lines that don't parse are skipped
+pub fn load(path: &Path) -> io::Result<Vec<Entry>> {
+    match fs::read_to_string(path) {
+        Ok(history) => Ok(history.lines().filter_map(Entry::from_line).collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn append(path: &Path, entry: &Entry) -> io::Result<()> {
+    OpenOptions::new().create(true).append(true).open(path)?.write_all(entry.to_line().as_bytes())
+}
+
+// FNV-1a, which unlike `DefaultHasher` is the same from one Rust release to the next
+#[derive(Clone, Copy)]
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn update(self, bytes: &[u8]) -> Fnv {
+        Fnv(bytes.iter().fold(self.0, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)))
+    }
+
+    fn hex(self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+pub fn hash_input<R: BufRead>(mut input: R) -> io::Result<String> {
+    let mut hash = Fnv::new();
+    loop {
+        let buffer = input.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        hash = hash.update(buffer);
+        let length = buffer.len();
+        input.consume(length);
+    }
+    Ok(hash.hex())
+}
+
+// Hashes the input as a run reads it, so recording the run doesn't have to open, decompress
+// and decrypt it all over again
+pub struct HashingReader<R> {
+    inner: R,
+    hash: Rc<Cell<Fnv>>,
+}
+
+// The hash of everything read through its `HashingReader` so far
+pub struct InputHash(Rc<Cell<Fnv>>);
+
+impl InputHash {
+    pub fn hex(&self) -> String {
+        self.0.get().hex()
+    }
+}
+
+pub fn hashing<R: Read>(inner: R) -> (HashingReader<R>, InputHash) {
+    let hash = Rc::new(Cell::new(Fnv::new()));
+    (HashingReader { inner, hash: Rc::clone(&hash) }, InputHash(hash))
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buffer)?;
+        self.hash.set(self.hash.get().update(&buffer[..read]));
+        Ok(read)
+    }
+}
+
+// The command line arguments that can change what a run solves, e.g. `--k 3`
+pub fn options<I: IntoIterator<Item = String>>(args: I) -> String {
+    let mut options = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if NOT_SOLVING_VALUES.contains(&arg.as_str()) {
+            args.next();
+        } else if !NOT_SOLVING_FLAGS.contains(&arg.as_str()) {
+            options.push(arg.replace(['\t', '\n'], " "));
+        }
+    }
+    options.join(" ")
+}
+
+fn commit() -> String {
+    Command::new("git").args(["rev-parse", "--short", "HEAD"]).output().ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+// Called by `run` and `run_parallel` once a part is done, with the hash of the input it read
+pub fn record<P: ?Sized>(report: &RunReport, part: usize, input_hash: String) {
+    // The library's own tests run puzzles too, and those shouldn't leave history behind
+    if cfg!(test) || args::flag("--no-history") {
+        return;
+    }
+
+    let name = any::type_name::<P>();
+    let entry = Entry {
+        time: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+        day: name.split("::").next().unwrap_or(name).to_string(),
+        part,
+        puzzle: name.rsplit("::").next().unwrap_or(name).to_string(),
+        commit: commit(),
+        input_hash,
+        run: report.run,
+        result: report.result.clone(),
+        // `./aoc run` passes the command on, `cargo run` doesn't
+        options: options(std::env::args().skip(1 + args::command().iter().count())),
+    };
+
+    let path = Path::new(FILE);
+    let history = load(path).unwrap_or_default();
+    if let Err(e) = append(path, &entry) {
+        eprintln!("Failed to record history in {}: {}", FILE, e);
+    }
+    if let Some(r) = regression(&history, &entry, margin()) {
+        eprintln!("REGRESSION: part {} took {}, {:.0}% slower than the median of its last {} runs ({})\n",
+                  entry.part, fmt_dur(entry.run), r.percent, r.samples, fmt_dur(r.median));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(part: usize, input_hash: &str, run_ms: u64) -> Entry {
+        Entry {
+            time: 1_600_000_000,
+            day: String::from("day_08"),
+            part,
+            puzzle: String::from("Puzzle1"),
+            commit: String::from("426d52e"),
+            input_hash: String::from(input_hash),
+            run: Duration::from_millis(run_ms),
+            result: String::from("a: 1928; b: 1319"),
+            options: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_line_round_trip() {
+        let e = entry(1, "00ff", 12);
+        assert_eq!(Some(e.clone()), Entry::from_line(e.to_line().trim_end()));
+        assert_eq!(None, Entry::from_line("1600000000\tday_08\tnot a part"));
+
+        let mut tabbed = e.clone();
+        tabbed.result = String::from("a\tb\nc");
+        assert_eq!("a b c", Entry::from_line(tabbed.to_line().trim_end()).unwrap().result);
+
+        let mut with_options = e.clone();
+        with_options.options = String::from("--k 3");
+        assert_eq!(Some(with_options.clone()), Entry::from_line(with_options.to_line().trim_end()));
+        let old = "1600000000\tday_08\t1\tPuzzle1\t426d52e\t00ff\t12000\ta: 1928; b: 1319";
+        assert_eq!(Some(e), Entry::from_line(old));
+    }
+
+    #[test]
+    fn test_options() {
+        let args = |a: &str| a.split(' ').map(String::from).collect::<Vec<String>>();
+        assert_eq!("", options(Vec::new()));
+        assert_eq!("--k 3 --precision big", options(args("--k 3 --no-history --precision big --trace stderr")));
+        assert_eq!("--slopes 1,1", options(args("--input big.txt.gz --alloc --slopes 1,1 --snapshot solved")));
+    }
+
+    #[test]
+    fn test_regression() {
+        let history = vec![entry(1, "aa", 10), entry(1, "aa", 12), entry(2, "aa", 100), entry(1, "bb", 100), entry(1, "aa", 11)];
+
+        assert!(regression(&history, &entry(1, "aa", 13), DEFAULT_MARGIN).is_none());
+        assert!(compare(&history, &entry(1, "aa", 9)).unwrap().percent < 0.0);
+        let slow = regression(&history, &entry(1, "aa", 20), DEFAULT_MARGIN).unwrap();
+        assert_eq!(Duration::from_millis(11), slow.median);
+        assert_eq!(3, slow.samples);
+        assert!(regression(&history, &entry(1, "aa", 20), 100.0).is_none());
+
+        // Too few runs of part 2, and none on input cc, to compare with
+        assert!(regression(&history, &entry(2, "aa", 1000), DEFAULT_MARGIN).is_none());
+        assert!(regression(&history, &entry(1, "cc", 1000), DEFAULT_MARGIN).is_none());
+
+        // Nor with other options, another puzzle or another answer
+        let mut other = entry(1, "aa", 1000);
+        other.options = String::from("--k 3");
+        assert!(regression(&history, &other, DEFAULT_MARGIN).is_none());
+        let mut other = entry(1, "aa", 1000);
+        other.puzzle = String::from("Puzzle2");
+        assert!(regression(&history, &other, DEFAULT_MARGIN).is_none());
+        let mut other = entry(1, "aa", 1000);
+        other.result = String::from("a: 1");
+        assert!(regression(&history, &other, DEFAULT_MARGIN).is_none());
+    }
+
+    #[test]
+    fn test_hash_input() {
+        assert_eq!("cbf29ce484222325", hash_input("".as_bytes()).unwrap());
+        assert_eq!("af63dc4c8601ec8c", hash_input("a".as_bytes()).unwrap());
+        assert_ne!(hash_input("1\n2\n".as_bytes()).unwrap(), hash_input("2\n1\n".as_bytes()).unwrap());
+
+        // Hashing while reading gives the same as hashing afterwards
+        let input = "1\r\n2\n\n3";
+        let (reader, hash) = hashing(input.as_bytes());
+        let lines: Vec<String> = io::BufReader::with_capacity(2, reader).lines().map(|l| l.unwrap()).collect();
+        assert_eq!(vec!["1", "2", "", "3"], lines);
+        assert_eq!(hash_input(input.as_bytes()).unwrap(), hash.hex());
+    }
+
+    #[test]
+    fn test_median() {
+        let ms = |v: &[u64]| v.iter().map(|m| Duration::from_millis(*m)).collect::<Vec<Duration>>();
+        assert_eq!(Duration::from_millis(5), median(&mut ms(&[9, 1, 5])));
+        assert_eq!(Duration::from_millis(4), median(&mut ms(&[9, 1, 3, 5])));
+    }
+}
@@ -1,5 +1,25 @@
-use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
 use std::time::{Instant, Duration};
+use alloc::PhaseMeter;
+use observer::Event;
+use snapshot::{Request, Stage};
+
+pub mod alloc;
+pub mod args;
+pub mod generate;
+pub mod history;
+pub mod lint;
+pub mod observer;
+pub mod snapshot;
+pub mod source;
+pub mod variants;
+pub mod vault;
+mod parallel;
+mod report;
+
+pub use parallel::ParallelPuzzle;
+pub use report::{fmt_dur, fmt_table, PhaseReport, RunReport};
 
 pub enum FilteredInputLine {
     Process,
@@ -32,79 +52,118 @@ pub trait Puzzle {
         self.default_filter_line(line)
     }
 
-    fn input(&self) -> String {
-        let input_filename = String::from("input.txt");
-        fs::read_to_string(input_filename)
-            .expect("Failed to read file")
+    // Lines are read as the run goes, so a big compressed input is never held in memory whole
+    fn input(&self) -> Box<dyn BufRead> {
+        open_input()
     }
 
-    fn run_with_input(&mut self, input: String) {
+    // JSON of the puzzle's state for `--snapshot`. Puzzles opt in with `snapshot::to_json`
+    // when built with the `serde` feature.
+    fn snapshot(&self) -> Option<String> {
+        None
+    }
+
+    fn run_with_input(&mut self, input: String) -> RunReport {
+        self.run_with_lines(input.lines())
+    }
+
+    fn run_with_lines<L: IntoIterator<Item = S>, S: AsRef<str>>(&mut self, lines: L) -> RunReport {
+        self.run_with_lines_and_snapshot(lines, None)
+    }
+
+    // As `run_with_lines`, also dumping the puzzle at `snapshot_at`. Only `run` takes this from
+    // the command line.
+    fn run_with_lines_and_snapshot<L: IntoIterator<Item = S>, S: AsRef<str>>(&mut self, lines: L, snapshot_at: Option<Request>) -> RunReport {
         let run_start = Instant::now();
+        let mut parse_duration = Duration::default();
         let mut process_durations = Vec::new();
+        let mut parse_meter = PhaseMeter::default();
+        let mut process_meter = PhaseMeter::default();
+        let mut final_meter = PhaseMeter::default();
 
-        for line in input.lines() {
+        for (line_index, line) in lines.into_iter().enumerate() {
+            let line = line.as_ref();
             if let FilteredInputLine::Skip = self.filter_line(line) {
                 continue;
             }
+            let parse_start = Instant::now();
+            parse_meter.begin();
             let item = self.parse_line(line);
+            parse_meter.end();
+            parse_duration += parse_start.elapsed();
+            observer::emit(|| Event::LineParsed { line: line_index + 1 });
 
             let processing_start = Instant::now();
+            process_meter.begin();
             self.process_item(item);
+            process_meter.end();
             process_durations.push(processing_start.elapsed());
+            observer::emit(|| Event::ItemProcessed { index: process_durations.len() - 1 });
         }
+        snapshot::dump(self, Stage::Parsed, snapshot_at);
 
         let final_result_start = Instant::now();
+        final_meter.begin();
         let result = self.final_result();
+        final_meter.end();
         let final_result_duration = final_result_start.elapsed();
+        snapshot::dump(self, Stage::Solved, snapshot_at);
 
         let run_duration = run_start.elapsed();
 
-        let avg_process = process_durations.iter().sum::<Duration>() / process_durations.len() as u32;
-        let min_process = process_durations.iter().min().expect("No min!");
-        let max_process = process_durations.iter().max().expect("No max!");
+        let process_count = process_durations.len().max(1) as u32;
+        let avg_process = process_durations.iter().sum::<Duration>() / process_count;
+        let min_process = process_durations.iter().min().copied().unwrap_or_default();
+        let max_process = process_durations.iter().max().copied().unwrap_or_default();
 
-        println!("Result: {} (run: {}, process: {} (min: {}, max: {}), final: {})\n",
-                 result,
-                 fmt_dur(run_duration),
-                 fmt_dur(avg_process), fmt_dur(*min_process), fmt_dur(*max_process),
-                 fmt_dur(final_result_duration));
+        RunReport {
+            result,
+            run: run_duration,
+            phases: vec![
+                PhaseReport { name: "parse", duration: parse_duration, spread: None, alloc: parse_meter.stats() },
+                PhaseReport { name: "process", duration: avg_process, spread: Some((min_process, max_process)), alloc: process_meter.stats() },
+                PhaseReport { name: "final", duration: final_result_duration, spread: None, alloc: final_meter.stats() },
+            ],
+            threads: 1,
+        }
     }
 
-    fn run(&mut self) {
-        self.run_with_input(self.input());
+    fn run(&mut self) -> RunReport {
+        observer::install_from_args();
+        if args::flag("--alloc") {
+            alloc::enable(true);
+        }
+        let part = history::next_part();
+        let (input, input_hash) = history::hashing(self.input());
+        let lines = BufReader::new(input).lines().map(|l| l.expect("Failed to read input"));
+        let report = self.run_with_lines_and_snapshot(lines, snapshot::requested(part));
+        println!("{}\n", report);
+        history::record::<Self>(&report, part, input_hash.hex());
+        report
     }
 }
 
-fn fmt_time(ms: f64) -> String {
-    if ms <= 1.0 {
-        let micro_sec = ms * 1000.0;
-        return format!("{}µs", micro_sec.round());
-    }
-    if ms < 1000.0 {
-        let whole_ms = ms.floor();
-        let rem_ms = ms - whole_ms;
-        return format!("{}ms ", whole_ms) + &fmt_time(rem_ms);
-    }
-    let sec: f64 = ms / 1000.0;
-    if sec < 60.0 {
-        let whole_sec = sec.floor();
-        let rem_ms = ms - whole_sec * 1000.0;
-        return format!("{}s ", whole_sec) + &fmt_time(rem_ms);
-    }
-    let min: f64 = sec / 60.0;
-
-    format!("{}m ", min.floor()) + &fmt_time((sec % 60.0) * 1000.0)
+// `input.txt`, or `--input <path>`, either of which may be stored compressed or encrypted,
+// see `source`
+pub fn open_input() -> Box<dyn BufRead> {
+    let input_filename = args::value("--input").unwrap_or_else(|| String::from("input.txt"));
+    source::open(Path::new(&input_filename))
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", input_filename, e))
 }
 
-fn fmt_dur(dur: Duration) -> String {
-    fmt_time(dur.as_secs_f64() * 1000.0)
+pub fn read_input() -> String {
+    let mut input = String::new();
+    open_input().read_to_string(&mut input).expect("Failed to read input");
+    input
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[global_allocator]
+    static ALLOC: alloc::CountingAllocator = alloc::CountingAllocator;
+
     #[derive(Default)]
     struct IntegerAdder {
         total: i64
@@ -121,8 +180,8 @@ mod tests {
             self.total.to_string()
         }
 
-        fn input(&self) -> String {
-            String::from("1\n2\n3\n")
+        fn input(&self) -> Box<dyn BufRead> {
+            Box::new(std::io::Cursor::new("1\n2\n3\n"))
         }
     }
 
@@ -142,6 +201,117 @@ mod tests {
         }
     }
 
+    #[derive(Default)]
+    struct SquareSummer {
+        squares: Vec<i64>,
+    }
+
+    impl Puzzle for SquareSummer {
+        type ParsedLine = i64;
+
+        fn process_item(&mut self, item: Self::ParsedLine) {
+            let squared = self.map_item(item);
+            self.reduce_item(squared);
+        }
+
+        fn final_result(&mut self) -> String {
+            format!("{:?}", self.squares)
+        }
+    }
+
+    impl ParallelPuzzle for SquareSummer {
+        type Mapped = i64;
+
+        fn map_item(&self, item: Self::ParsedLine) -> Self::Mapped {
+            item * item
+        }
+
+        fn reduce_item(&mut self, mapped: Self::Mapped) {
+            self.squares.push(mapped);
+        }
+
+        fn threads(&self) -> usize {
+            3
+        }
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let input: String = (1..=50).map(|i| format!("{}\n\n", i)).collect();
+
+        let mut sequential: SquareSummer = Default::default();
+        let sequential_report = sequential.run_with_input(input.clone());
+
+        let mut parallel: SquareSummer = Default::default();
+        let parallel_report = parallel.run_parallel_with_input(input);
+
+        assert_eq!(sequential_report.result, parallel_report.result);
+        assert_eq!(3, parallel_report.threads);
+        assert!(parallel_report.phase("map").is_some());
+    }
+
+    // The observer is process wide, so only events from the recording test's own thread are
+    // kept. Puzzles run by other tests at the same time are ignored.
+    struct Recorder {
+        thread: std::thread::ThreadId,
+        events: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl observer::Observer for Recorder {
+        fn event(&mut self, event: &Event) {
+            if std::thread::current().id() == self.thread {
+                self.events.lock().unwrap().push(event.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_observer_events() {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        observer::set_observer(Some(Box::new(Recorder { thread: std::thread::current().id(), events: events.clone() })));
+
+        let mut subject: IntegerAdder = Default::default();
+        subject.run_with_input(String::from("1\n\n3\n"));
+        observer::counter("answer", 4);
+        observer::set_observer(None);
+
+        let events = events.lock().unwrap();
+        assert_eq!(vec!["line_parsed\t1", "item_processed\t0", "line_parsed\t3", "item_processed\t1", "counter\tanswer\t4"], *events);
+    }
+
+    // Puts allocation counting back how it was, even if the test fails
+    struct CountingState(bool);
+
+    impl Drop for CountingState {
+        fn drop(&mut self) {
+            alloc::enable(self.0);
+        }
+    }
+
+    #[test]
+    fn test_alloc_accounting() {
+        let _restore = CountingState(alloc::is_enabled());
+        alloc::enable(true);
+
+        let mut meter = PhaseMeter::default();
+        meter.begin();
+        let block = vec![0u8; 4096];
+        meter.end();
+        drop(block);
+        let stats = meter.stats().unwrap();
+        assert!(stats.allocations >= 1);
+        assert!(stats.bytes >= 4096);
+
+        let mut subject = StringCatter { result: String::new() };
+        let report = subject.run_with_input(String::from("abc\ndef\nghi\n"));
+
+        let parse = report.phase("parse").unwrap().alloc.unwrap();
+        assert!(parse.allocations >= 3);
+        assert!(parse.bytes >= 9);
+        assert!(report.phase("process").unwrap().alloc.is_some());
+        assert!(report.to_string().contains("allocs: "));
+    }
+
     #[test]
     fn test_input_override() {
         let mut subject: IntegerAdder = Default::default();
@@ -149,6 +319,16 @@ mod tests {
         assert_eq!("6", subject.final_result());
     }
 
+    #[test]
+    fn test_compressed_lines() {
+        let compressed = ruzstd::encoding::compress_to_vec("1\n\n2\n3\n".as_bytes(), ruzstd::encoding::CompressionLevel::Fastest);
+        let reader = source::decompress(Box::new(std::io::Cursor::new(compressed))).unwrap();
+
+        let mut subject: IntegerAdder = Default::default();
+        let report = subject.run_with_lines(reader.lines().map(Result::unwrap));
+        assert_eq!("6", report.result);
+    }
+
     #[test]
     fn test_input() {
         let mut subject = StringCatter { result: String::new() };
@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::fmt;
+use regex::Regex;
+
+#[derive(Debug, PartialEq)]
+pub struct Problem {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+// Lightweight description of what a day's input should look like, checked before any solving
+// code gets a chance to panic on it. Blank lines are ignored unless the input is made of
+// blank line separated records.
+#[derive(Default)]
+pub struct Schema {
+    allowed_chars: Option<String>,
+    line_pattern: Option<Regex>,
+    token_pattern: Option<Regex>,
+    consistent_width: bool,
+    records: bool,
+    unique_keys: bool,
+}
+
+impl Schema {
+    pub fn new() -> Schema {
+        Default::default()
+    }
+
+    pub fn allowed_chars(mut self, chars: &str) -> Schema {
+        self.allowed_chars = Some(chars.to_string());
+        self
+    }
+
+    // Anchored at both ends, so the whole line has to match
+    pub fn line_pattern(mut self, pattern: &str) -> Schema {
+        self.line_pattern = Some(Regex::new(&format!("^(?:{})$", pattern)).expect("Bad line pattern"));
+        self
+    }
+
+    // Every whitespace separated token has to match
+    pub fn token_pattern(mut self, pattern: &str) -> Schema {
+        self.token_pattern = Some(Regex::new(&format!("^(?:{})$", pattern)).expect("Bad token pattern"));
+        self
+    }
+
+    pub fn consistent_width(mut self) -> Schema {
+        self.consistent_width = true;
+        self
+    }
+
+    pub fn records(mut self) -> Schema {
+        self.records = true;
+        self
+    }
+
+    // Tokens of the form `key:value` may only use each key once per record (or line)
+    pub fn unique_keys(mut self) -> Schema {
+        self.unique_keys = true;
+        self
+    }
+
+    pub fn lint(&self, input: &str) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let mut width: Option<(usize, usize)> = None;
+        let mut keys: HashSet<String> = HashSet::new();
+        let mut previous_blank = true;
+
+        for (line_index, line) in input.lines().enumerate() {
+            let line_number = line_index + 1;
+            let mut problem = |column: usize, message: String| {
+                problems.push(Problem { line: line_number, column, message });
+            };
+
+            if line.trim().is_empty() {
+                if self.records && previous_blank && line_index > 0 {
+                    problem(1, String::from("empty record"));
+                }
+                previous_blank = true;
+                keys.clear();
+                continue;
+            }
+            previous_blank = false;
+            if !self.records {
+                keys.clear();
+            }
+
+            if let Some(allowed) = &self.allowed_chars {
+                for (column, c) in line.chars().enumerate() {
+                    if !allowed.contains(c) {
+                        problem(column + 1, format!("unexpected character {:?}, expected one of {:?}", c, allowed));
+                    }
+                }
+            }
+
+            if let Some(pattern) = &self.line_pattern {
+                if !pattern.is_match(line) {
+                    problem(1, format!("line {:?} doesn't match {}", line, pattern));
+                }
+            }
+
+            if self.token_pattern.is_some() || self.unique_keys {
+                for (column, token) in tokens(line) {
+                    if let Some(pattern) = &self.token_pattern {
+                        if !pattern.is_match(token) {
+                            problem(column, format!("token {:?} doesn't match {}", token, pattern));
+                        }
+                    }
+                    if self.unique_keys {
+                        if let Some((key, _)) = token.split_once(':') {
+                            if !keys.insert(key.to_string()) {
+                                problem(column, format!("duplicate key {:?}", key));
+                            }
+                        }
+                    }
+                }
+            }
+
+            if self.consistent_width {
+                let line_width = line.chars().count();
+                match width {
+                    None => width = Some((line_width, line_number)),
+                    Some((expected, first_line)) if expected != line_width => {
+                        problem(expected.min(line_width) + 1,
+                                format!("line is {} wide, line {} is {} wide", line_width, first_line, expected));
+                    },
+                    _ => {},
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+// 1 based character column of each whitespace separated token
+fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let mut found = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    for (column, (byte, c)) in line.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((column, byte)),
+            (true, Some((start_column, start_byte))) => {
+                found.push((start_column + 1, &line[start_byte..byte]));
+                start = None;
+            },
+            _ => {},
+        }
+    }
+    if let Some((start_column, start_byte)) = start {
+        found.push((start_column + 1, &line[start_byte..]));
+    }
+    found
+}
+
+// Prints every problem and returns how many there were
+pub fn report(schema: &Schema, input: &str) -> usize {
+    let problems = schema.lint(input);
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    if problems.is_empty() {
+        println!("Input OK");
+    } else {
+        println!("{} problem(s)", problems.len());
+    }
+    problems.len()
+}
+
+// `lint` command for a day's binary: check input.txt and exit non-zero if anything is wrong
+pub fn run(schema: &Schema) {
+    let problems = report(schema, &crate::read_input());
+    std::process::exit(if problems == 0 { 0 } else { 1 });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let schema = Schema::new().allowed_chars("L.").consistent_width();
+        let problems = schema.lint("L.L\nLL#\n\nL.\n");
+
+        assert_eq!(vec![
+            Problem { line: 2, column: 3, message: String::from("unexpected character '#', expected one of \"L.\"") },
+            Problem { line: 4, column: 3, message: String::from("line is 2 wide, line 1 is 3 wide") },
+        ], problems);
+    }
+
+    #[test]
+    fn test_records() {
+        let schema = Schema::new().records().token_pattern(r"[a-z]{3}:\S+").unique_keys();
+        let problems = schema.lint("ecl:gry pid:1\nhcl:#fff ecl:amb\n\n\nbyr1937\n\necl:gry\n");
+
+        assert_eq!(3, problems.len());
+        assert_eq!((2, 10), (problems[0].line, problems[0].column));
+        assert!(problems[0].message.contains("duplicate key"));
+        assert_eq!((4, 1), (problems[1].line, problems[1].column));
+        assert_eq!((5, 1), (problems[2].line, problems[2].column));
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+
+pub enum Event {
+    LineParsed { line: usize },
+    ItemProcessed { index: usize },
+    SimulationStep { step: usize, state: Option<String> },
+    Counter { name: &'static str, value: i64 },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::LineParsed { line } => write!(f, "line_parsed\t{}", line),
+            Event::ItemProcessed { index } => write!(f, "item_processed\t{}", index),
+            Event::SimulationStep { step, state: None } => write!(f, "simulation_step\t{}", step),
+            Event::SimulationStep { step, state: Some(state) } => write!(f, "simulation_step\t{}\n{}", step, state),
+            Event::Counter { name, value } => write!(f, "counter\t{}\t{}", name, value),
+        }
+    }
+}
+
+pub trait Observer: Send {
+    fn event(&mut self, event: &Event);
+}
+
+pub struct StderrLog;
+
+impl Observer for StderrLog {
+    fn event(&mut self, event: &Event) {
+        eprintln!("{}", event);
+    }
+}
+
+pub struct EventFile {
+    out: BufWriter<File>,
+}
+
+impl EventFile {
+    pub fn create(path: &str) -> EventFile {
+        let file = File::create(path).expect("Failed to create event file");
+        EventFile { out: BufWriter::new(file) }
+    }
+}
+
+impl Observer for EventFile {
+    fn event(&mut self, event: &Event) {
+        writeln!(self.out, "{}", event).expect("Failed to write event");
+        self.out.flush().expect("Failed to write event");
+    }
+}
+
+static OBSERVER: Mutex<Option<Box<dyn Observer>>> = Mutex::new(None);
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static FROM_ARGS: Once = Once::new();
+
+pub fn set_observer(observer: Option<Box<dyn Observer>>) {
+    let mut current = OBSERVER.lock().unwrap();
+    ACTIVE.store(observer.is_some(), Ordering::Relaxed);
+    *current = observer;
+}
+
+// With no observer installed this is a single relaxed load; the event is never built.
+#[inline]
+pub fn emit<F: FnOnce() -> Event>(make_event: F) {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return;
+    }
+
+    if let Some(observer) = OBSERVER.lock().unwrap().as_mut() {
+        observer.event(&make_event());
+    }
+}
+
+#[inline]
+pub fn counter(name: &'static str, value: i64) {
+    emit(|| Event::Counter { name, value });
+}
+
+// `--trace stderr` logs to stderr, `--trace <path>` writes an event file, and
+// `--trace none` (or no flag) installs nothing. Only the first call looks at the args.
+pub fn install_from_args() {
+    FROM_ARGS.call_once(|| {
+        match crate::args::value("--trace").as_deref() {
+            None | Some("none") => {},
+            Some("stderr") => set_observer(Some(Box::new(StderrLog))),
+            Some(path) => set_observer(Some(Box::new(EventFile::create(path)))),
+        }
+    });
+}
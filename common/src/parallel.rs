@@ -0,0 +1,142 @@
+use std::thread;
+use std::time::Instant;
+use crate::{read_input, FilteredInputLine, PhaseReport, Puzzle, RunReport};
+use crate::alloc::{self, PhaseMeter};
+use crate::args;
+use crate::history;
+use crate::observer::{self, Event};
+use crate::snapshot::{self, Request, Stage};
+
+// Opt-in mode for puzzles whose per-line work doesn't depend on earlier lines. Lines are
+// filtered in order, then parsed and mapped across threads, then reduced in input order.
+//
+// Parsing always goes through `str::parse`, and `filter_line` runs before any item is
+// processed, so neither may rely on state built up by `process_item`. The sequential
+// `process_item` of an implementor should be `reduce_item(map_item(item))`.
+pub trait ParallelPuzzle: Puzzle + Sync
+    where Self::ParsedLine: Send {
+    type Mapped: Send;
+
+    fn map_item(&self, item: Self::ParsedLine) -> Self::Mapped;
+    fn reduce_item(&mut self, mapped: Self::Mapped);
+
+    fn threads(&self) -> usize {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    }
+
+    fn run_parallel_with_input(&mut self, input: String) -> RunReport {
+        self.run_parallel_with_input_and_snapshot(input, None)
+    }
+
+    // As `run_parallel_with_input`, also dumping the puzzle at `snapshot_at`
+    fn run_parallel_with_input_and_snapshot(&mut self, input: String, snapshot_at: Option<Request>) -> RunReport {
+        let run_start = Instant::now();
+        let threads = self.threads().max(1);
+
+        let mut lines = Vec::new();
+        for (line_index, line) in input.lines().enumerate() {
+            if let FilteredInputLine::Process = self.filter_line(line) {
+                lines.push((line_index + 1, line));
+            }
+        }
+
+        let parse_start = Instant::now();
+        let mut parse_meter = PhaseMeter::default();
+        parse_meter.begin();
+        let items = par_map(lines, threads, |(line_number, line)| match line.parse::<Self::ParsedLine>() {
+            Ok(i) => {
+                observer::emit(|| Event::LineParsed { line: line_number });
+                i
+            },
+            Err(_) => panic!()
+        });
+        parse_meter.end();
+        let parse_duration = parse_start.elapsed();
+
+        let map_start = Instant::now();
+        let mut map_meter = PhaseMeter::default();
+        map_meter.begin();
+        let shared: &Self = self;
+        let mapped = par_map(items, threads, |item| shared.map_item(item));
+        map_meter.end();
+        let map_duration = map_start.elapsed();
+
+        let reduce_start = Instant::now();
+        let mut reduce_meter = PhaseMeter::default();
+        reduce_meter.begin();
+        for (index, m) in mapped.into_iter().enumerate() {
+            self.reduce_item(m);
+            observer::emit(|| Event::ItemProcessed { index });
+        }
+        reduce_meter.end();
+        let reduce_duration = reduce_start.elapsed();
+        snapshot::dump(self, Stage::Parsed, snapshot_at);
+
+        let final_result_start = Instant::now();
+        let mut final_meter = PhaseMeter::default();
+        final_meter.begin();
+        let result = self.final_result();
+        final_meter.end();
+        let final_result_duration = final_result_start.elapsed();
+        snapshot::dump(self, Stage::Solved, snapshot_at);
+
+        RunReport {
+            result,
+            run: run_start.elapsed(),
+            phases: vec![
+                PhaseReport { name: "parse", duration: parse_duration, spread: None, alloc: parse_meter.stats() },
+                PhaseReport { name: "map", duration: map_duration, spread: None, alloc: map_meter.stats() },
+                PhaseReport { name: "reduce", duration: reduce_duration, spread: None, alloc: reduce_meter.stats() },
+                PhaseReport { name: "final", duration: final_result_duration, spread: None, alloc: final_meter.stats() },
+            ],
+            threads,
+        }
+    }
+
+    fn run_parallel(&mut self) -> RunReport {
+        observer::install_from_args();
+        if args::flag("--alloc") {
+            alloc::enable(true);
+        }
+        let part = history::next_part();
+        let input = read_input();
+        let input_hash = history::hash_input(input.as_bytes()).expect("Failed to hash input");
+        let report = self.run_parallel_with_input_and_snapshot(input, snapshot::requested(part));
+        println!("{}\n", report);
+        history::record::<Self>(&report, part, input_hash);
+        report
+    }
+}
+
+// Order preserving map over contiguous chunks, one scoped thread per chunk. A panic in a
+// worker is resumed on the calling thread.
+fn par_map<T, U, F>(items: Vec<T>, threads: usize, f: F) -> Vec<U>
+    where T: Send, U: Send, F: Fn(T) -> U + Sync {
+    if threads <= 1 || items.len() <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let chunk_size = items.len().div_ceil(threads);
+    let mut chunks = Vec::new();
+    let mut remaining = items.into_iter();
+    loop {
+        let chunk: Vec<T> = remaining.by_ref().take(chunk_size).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        chunks.push(chunk);
+    }
+
+    let f = &f;
+    thread::scope(|scope| {
+        let workers: Vec<_> = chunks.into_iter()
+            .map(|chunk| scope.spawn(move || chunk.into_iter().map(f).collect::<Vec<U>>()))
+            .collect();
+
+        workers.into_iter()
+            .flat_map(|w| w.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    })
+}
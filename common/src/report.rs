@@ -0,0 +1,108 @@
+use std::fmt;
+use std::time::Duration;
+use crate::alloc::AllocStats;
+
+// Durations serialize as fractional microseconds, with the unit in the field name
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PhaseReport {
+    pub name: &'static str,
+    #[cfg_attr(feature = "serde", serde(rename = "duration_us", serialize_with = "micros"))]
+    pub duration: Duration,
+    #[cfg_attr(feature = "serde", serde(rename = "spread_us", serialize_with = "micros_spread"))]
+    pub spread: Option<(Duration, Duration)>,
+    pub alloc: Option<AllocStats>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RunReport {
+    pub result: String,
+    #[cfg_attr(feature = "serde", serde(rename = "run_us", serialize_with = "micros"))]
+    pub run: Duration,
+    pub phases: Vec<PhaseReport>,
+    pub threads: usize,
+}
+
+#[cfg(feature = "serde")]
+fn micros<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1_000_000.0)
+}
+
+#[cfg(feature = "serde")]
+fn micros_spread<S: serde::Serializer>(spread: &Option<(Duration, Duration)>, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+    spread.map(|(min, max)| (min.as_secs_f64() * 1_000_000.0, max.as_secs_f64() * 1_000_000.0))
+        .serialize(serializer)
+}
+
+impl RunReport {
+    pub fn phase(&self, name: &str) -> Option<&PhaseReport> {
+        self.phases.iter().find(|p| p.name == name)
+    }
+}
+
+impl fmt::Display for PhaseReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, fmt_dur(self.duration))?;
+        if let Some((min, max)) = self.spread {
+            write!(f, " (min: {}, max: {})", fmt_dur(min), fmt_dur(max))?;
+        }
+        if let Some(alloc) = self.alloc {
+            write!(f, " [{}]", alloc)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RunReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Result: {} (run: {}", self.result, fmt_dur(self.run))?;
+        for phase in &self.phases {
+            write!(f, ", {}", phase)?;
+        }
+        if self.threads > 1 {
+            write!(f, ", threads: {}", self.threads)?;
+        }
+        write!(f, ")")
+    }
+}
+
+fn fmt_time(ms: f64) -> String {
+    if ms <= 1.0 {
+        let micro_sec = ms * 1000.0;
+        return format!("{}µs", micro_sec.round());
+    }
+    if ms < 1000.0 {
+        let whole_ms = ms.floor();
+        let rem_ms = ms - whole_ms;
+        return format!("{}ms ", whole_ms) + &fmt_time(rem_ms);
+    }
+    let sec: f64 = ms / 1000.0;
+    if sec < 60.0 {
+        let whole_sec = sec.floor();
+        let rem_ms = ms - whole_sec * 1000.0;
+        return format!("{}s ", whole_sec) + &fmt_time(rem_ms);
+    }
+    let min: f64 = sec / 60.0;
+
+    format!("{}m ", min.floor()) + &fmt_time((sec % 60.0) * 1000.0)
+}
+
+pub fn fmt_dur(dur: Duration) -> String {
+    fmt_time(dur.as_secs_f64() * 1000.0)
+}
+
+// Left aligned columns, two spaces apart. The first row is the headings.
+pub fn fmt_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| rows.iter().filter_map(|r| r.get(c)).map(|s| s.chars().count()).max().unwrap_or(0))
+        .collect();
+
+    let mut out = String::new();
+    for row in rows {
+        let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, w)| format!("{:<w$}", cell, w = w)).collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
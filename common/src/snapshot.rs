@@ -0,0 +1,103 @@
+use std::any;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::{args, Puzzle};
+
+// Points in a run where `--snapshot` can dump a puzzle. `Parsed` is once every line has
+// been through `process_item`, `Solved` is after `final_result`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Stage {
+    Parsed,
+    Solved,
+}
+
+impl Stage {
+    fn name(self) -> &'static str {
+        match self {
+            Stage::Parsed => "parsed",
+            Stage::Solved => "solved",
+        }
+    }
+}
+
+// A snapshot to take of one part, numbered like its history so parts that run the same
+// puzzle type don't overwrite each other
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Request {
+    pub stage: Stage,
+    pub part: usize,
+}
+
+// The stage given by `--snapshot <stage>`, read by the binaries' `run` for the part it's running
+pub fn requested(part: usize) -> Option<Request> {
+    let stage = match args::value("--snapshot").as_deref() {
+        None => return None,
+        Some("parsed") => Stage::Parsed,
+        Some("solved") => Stage::Solved,
+        Some(other) => panic!("Bad --snapshot {:?}, expected parsed or solved", other),
+    };
+    Some(Request { stage, part })
+}
+
+// e.g. `snapshots/Puzzle1.2.solved.json`
+fn path(name: &str, request: Request) -> PathBuf {
+    Path::new("snapshots").join(format!("{}.{}.{}.json", name, request.part, request.stage.name()))
+}
+
+// Writes `snapshots/<Puzzle>.<part>.<stage>.json` under the working directory when `stage` is
+// the one asked for
+pub fn dump<P: Puzzle + ?Sized>(puzzle: &P, stage: Stage, requested: Option<Request>) {
+    let request = match requested {
+        Some(request) if request.stage == stage => request,
+        _ => return,
+    };
+
+    let name = any::type_name::<P>().rsplit("::").next().unwrap_or("puzzle");
+    match puzzle.snapshot() {
+        Some(json) => {
+            let path = path(name, request);
+            fs::create_dir_all("snapshots").expect("Failed to create snapshots directory");
+            fs::write(&path, json + "\n").expect("Failed to write snapshot");
+            println!("Snapshot written to {}", path.display());
+        },
+        None => eprintln!("{} has no snapshot, build with --features serde", name),
+    }
+}
+
+#[cfg(feature = "serde")]
+pub fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string_pretty(value).expect("Failed to serialize snapshot")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_snapshot_requested() {
+        assert_eq!(None, requested(1));
+        assert_eq!("solved", Stage::Solved.name());
+    }
+
+    #[test]
+    fn test_path() {
+        let casual = path("Puzzle1", Request { stage: Stage::Solved, part: 1 });
+        let strict = path("Puzzle1", Request { stage: Stage::Solved, part: 2 });
+        assert_eq!(Path::new("snapshots/Puzzle1.1.solved.json"), casual);
+        assert_eq!(Path::new("snapshots/Puzzle1.2.solved.json"), strict);
+        assert_eq!(Path::new("snapshots/Puzzle1.2.parsed.json"), path("Puzzle1", Request { stage: Stage::Parsed, part: 2 }));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_to_json() {
+        #[derive(serde::Serialize)]
+        struct Cpu {
+            acc: i64,
+            trace: Vec<&'static str>,
+        }
+
+        let json = to_json(&Cpu { acc: -3, trace: vec!["nop"] });
+        assert_eq!("{\n  \"acc\": -3,\n  \"trace\": [\n    \"nop\"\n  ]\n}", json);
+    }
+}
@@ -0,0 +1,139 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use flate2::bufread::MultiGzDecoder;
+use ruzstd::decoding::StreamingDecoder;
+use crate::vault;
+
+// Where puzzle input comes from. A path that doesn't exist is looked for with each of these
+// suffixes added, so `input.txt` can be stored as `input.txt.gz`, `input.txt.zst` or
+// `input.txt.enc`, or compressed and then encrypted as `input.txt.gz.enc`.
+const SUFFIXES: [&str; 3] = ["gz", "zst", vault::EXTENSION];
+const COMPRESSIONS: [&str; 2] = ["gz", "zst"];
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+pub fn resolve(path: &Path) -> PathBuf {
+    if path.exists() {
+        return path.to_path_buf();
+    }
+    let stacked = COMPRESSIONS.iter().map(|s| with_suffix(&with_suffix(path, s), vault::EXTENSION));
+    SUFFIXES.iter()
+        .map(|s| with_suffix(path, s))
+        .chain(stacked)
+        .find(|p| p.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+// The name an input goes by without its storage suffixes, e.g. `alice.txt` for
+// `alice.txt.gz.enc`
+pub fn plain_path(path: &Path) -> PathBuf {
+    let mut path = path.to_path_buf();
+    while path.extension().is_some_and(|e| SUFFIXES.iter().any(|s| e == *s)) {
+        path.set_extension("");
+    }
+    path
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// Compression is detected from the first bytes rather than the name, so it also works on
+// decrypted inputs. Compressed files are decoded as they're read, plain ones are buffered
+// from disk; only encrypted inputs are held in memory whole, to check them before use.
+pub fn open(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let path = resolve(path);
+    let raw: Box<dyn BufRead> = if vault::is_encrypted(&path) {
+        Box::new(Cursor::new(vault::read(&path)?))
+    } else {
+        Box::new(BufReader::new(File::open(&path)?))
+    };
+    decompress(raw)
+}
+
+pub fn decompress<'a>(mut reader: Box<dyn BufRead + 'a>) -> io::Result<Box<dyn BufRead + 'a>> {
+    let start = reader.fill_buf()?;
+    if start.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else if start.starts_with(ZSTD_MAGIC) {
+        let decoder = StreamingDecoder::new(reader).map_err(|e| io::Error::new(ErrorKind::InvalidData, e.to_string()))?;
+        Ok(Box::new(BufReader::new(decoder)))
+    } else {
+        Ok(reader)
+    }
+}
+
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    let mut input = String::new();
+    open(path)?.read_to_string(&mut input)?;
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use ruzstd::encoding::{compress_to_vec, CompressionLevel};
+
+    const INPUT: &str = "nop +0\nacc +1\njmp +4\n";
+
+    fn gzip(input: &str) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(input.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn lines(compressed: Vec<u8>) -> Vec<String> {
+        decompress(Box::new(Cursor::new(compressed))).unwrap().lines().map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn test_decompress() {
+        let expected = vec!["nop +0", "acc +1", "jmp +4"];
+        assert_eq!(expected, lines(INPUT.as_bytes().to_vec()));
+        assert_eq!(expected, lines(gzip(INPUT)));
+        assert_eq!(expected, lines(compress_to_vec(INPUT.as_bytes(), CompressionLevel::Fastest)));
+        assert!(lines(Vec::new()).is_empty());
+
+        // Concatenated members, as from `cat a.gz b.gz`
+        let mut both = gzip("nop +0\n");
+        both.extend(gzip("acc +1\njmp +4\n"));
+        assert_eq!(expected, lines(both));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let dir = std::env::temp_dir().join(format!("aoc-source-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("plain.txt"), INPUT).unwrap();
+        fs::write(dir.join("big.txt.gz"), gzip(INPUT)).unwrap();
+        fs::write(dir.join("bigger.txt.zst"), compress_to_vec(INPUT.as_bytes(), CompressionLevel::Fastest)).unwrap();
+
+        let plain = read_to_string(&dir.join("plain.txt"));
+        let big = read_to_string(&dir.join("big.txt"));
+        let bigger = read_to_string(&dir.join("bigger.txt"));
+        let missing = read_to_string(&dir.join("missing.txt"));
+        let secret = resolve(&dir.join("secret.txt"));
+        fs::write(dir.join("stacked.txt.zst.enc"), "").unwrap();
+        let stacked = resolve(&dir.join("stacked.txt"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(INPUT, plain.unwrap());
+        assert_eq!(INPUT, big.unwrap());
+        assert_eq!(INPUT, bigger.unwrap());
+        assert_eq!(ErrorKind::NotFound, missing.unwrap_err().kind());
+        assert_eq!(dir.join("secret.txt"), secret);
+        assert_eq!(dir.join("stacked.txt.zst.enc"), stacked);
+        assert_eq!(Path::new("stacked.txt"), plain_path(stacked.strip_prefix(&dir).unwrap()));
+        assert_eq!(Path::new("alice.txt"), plain_path(Path::new("alice.txt.gz.enc")));
+        assert_eq!(Path::new("alice.txt"), plain_path(Path::new("alice.txt")));
+    }
+}
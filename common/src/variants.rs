@@ -0,0 +1,117 @@
+use std::io::ErrorKind;
+use std::path::Path;
+use crate::{fmt_dur, fmt_table, read_input, source, Puzzle, RunReport};
+
+// One solving strategy for a puzzle. Every variant of a puzzle has to produce the same
+// result for the same input, which `assert_agree` checks.
+pub struct Variant<P> {
+    pub name: &'static str,
+    pub puzzle: fn() -> P,
+}
+
+pub fn compare<P: Puzzle>(variants: &[Variant<P>], input: &str) -> Vec<(&'static str, RunReport)> {
+    variants.iter()
+        .map(|v| {
+            let mut puzzle = (v.puzzle)();
+            (v.name, puzzle.run_with_input(input.to_string()))
+        })
+        .collect()
+}
+
+pub fn assert_agree<P: Puzzle>(variants: &[Variant<P>], input: &str) {
+    let results = compare(variants, input);
+    let (expected_name, expected) = &results[0];
+    for (name, report) in &results[1..] {
+        assert_eq!(expected.result, report.result, "{} and {} disagree", expected_name, name);
+    }
+}
+
+// The day's own `input.txt` for tests to check variants against, however it's stored. None
+// when this checkout doesn't have it, or can't decrypt it.
+pub fn real_input() -> Option<String> {
+    match source::read_to_string(Path::new("input.txt")) {
+        Ok(input) => Some(input),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => panic!("Failed to read input.txt: {}", e),
+    }
+}
+
+pub fn table(results: &[(&'static str, RunReport)]) -> String {
+    let mut headings = vec![String::from("variant"), String::from("result"), String::from("run")];
+    if let Some((_, report)) = results.first() {
+        headings.extend(report.phases.iter().map(|p| p.name.to_string()));
+    }
+
+    let mut rows = vec![headings];
+    for (name, report) in results {
+        let mut row = vec![name.to_string(), report.result.clone(), fmt_dur(report.run)];
+        row.extend(report.phases.iter().map(|p| fmt_dur(p.duration)));
+        rows.push(row);
+    }
+
+    fmt_table(&rows)
+}
+
+// `variants` command for a day's binary: runs every variant on the input and shows the
+// timings side by side
+pub fn run<P: Puzzle>(variants: &[Variant<P>]) {
+    let results = compare(variants, &read_input());
+    print!("{}", table(&results));
+
+    // Disagreeing variants fail the command, so it works as a check
+    let agree = results.iter().all(|(_, r)| r.result == results[0].1.result);
+    println!("{}\n", if agree { "All variants agree" } else { "VARIANTS DISAGREE" });
+    if !agree {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Summer {
+        values: Vec<i64>,
+        by_formula: bool,
+    }
+
+    impl Puzzle for Summer {
+        type ParsedLine = i64;
+
+        fn process_item(&mut self, item: Self::ParsedLine) {
+            self.values.push(item);
+        }
+
+        fn final_result(&mut self) -> String {
+            if self.by_formula {
+                let n = self.values.len() as i64;
+                (n * (n + 1) / 2).to_string()
+            } else {
+                self.values.iter().sum::<i64>().to_string()
+            }
+        }
+    }
+
+    fn variants() -> Vec<Variant<Summer>> {
+        vec![
+            Variant { name: "loop", puzzle: || Summer { values: Vec::new(), by_formula: false } },
+            Variant { name: "formula", puzzle: || Summer { values: Vec::new(), by_formula: true } },
+        ]
+    }
+
+    #[test]
+    fn test_agree() {
+        assert_agree(&variants(), "1\n2\n3\n4\n");
+
+        let results = compare(&variants(), "1\n2\n3\n4\n");
+        let table = table(&results);
+        assert!(table.starts_with("variant  result  run"));
+        assert!(table.contains("formula  10"));
+    }
+
+    #[test]
+    #[should_panic(expected = "loop and formula disagree")]
+    fn test_disagree() {
+        assert_agree(&variants(), "1\n5\n");
+    }
+}
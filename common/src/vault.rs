@@ -0,0 +1,155 @@
+use std::env;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+
+// Puzzle inputs stored encrypted at rest, e.g. `input.txt.enc` next to where `input.txt`
+// would be. Files are ChaCha20-Poly1305: a header, a random nonce, then the ciphertext.
+//
+// The key is 64 hex digits, taken from `AOC_INPUT_KEY`, or else from the file named by
+// `AOC_INPUT_KEY_FILE`, or else from `.aoc-key` in the working directory or any above it.
+pub const EXTENSION: &str = "enc";
+pub const KEY_ENV: &str = "AOC_INPUT_KEY";
+pub const KEY_FILE_ENV: &str = "AOC_INPUT_KEY_FILE";
+pub const KEY_FILE: &str = ".aoc-key";
+
+const HEADER: &[u8] = b"aoc-input v1\n";
+const NONCE_LENGTH: usize = 12;
+
+pub struct Key([u8; 32]);
+
+impl Key {
+    pub fn generate() -> Key {
+        Key(ChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+
+    pub fn from_hex(hex: &str) -> io::Result<Key> {
+        let hex = hex.trim();
+        let bad_key = || io::Error::new(ErrorKind::InvalidData, "Input key should be 64 hex digits");
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(bad_key());
+        }
+
+        let mut key = [0; 32];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| bad_key())?;
+        }
+        Ok(Key(key))
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    // From the environment or the nearest key file, see the top of this module
+    pub fn find() -> io::Result<Key> {
+        if let Ok(hex) = env::var(KEY_ENV) {
+            return Key::from_hex(&hex);
+        }
+        if let Ok(path) = env::var(KEY_FILE_ENV) {
+            return Key::from_hex(&fs::read_to_string(path)?);
+        }
+
+        let dir = env::current_dir()?;
+        match dir.ancestors().map(|d| d.join(KEY_FILE)).find(|p| p.is_file()) {
+            Some(path) => Key::from_hex(&fs::read_to_string(path)?),
+            None => Err(io::Error::new(ErrorKind::NotFound,
+                                       format!("No input key: set {} or {}, or create {}", KEY_ENV, KEY_FILE_ENV, KEY_FILE))),
+        }
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(&self.0.into())
+    }
+}
+
+pub fn encrypt(key: &Key, plain: &[u8]) -> Vec<u8> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let sealed = key.cipher().encrypt(&nonce, plain).expect("Failed to encrypt");
+
+    let mut out = HEADER.to_vec();
+    out.extend_from_slice(&nonce);
+    out.extend(sealed);
+    out
+}
+
+pub fn decrypt(key: &Key, data: &[u8]) -> io::Result<Vec<u8>> {
+    let body = data.strip_prefix(HEADER)
+        .filter(|b| b.len() >= NONCE_LENGTH)
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "Not an encrypted input"))?;
+    let (nonce, sealed) = body.split_at(NONCE_LENGTH);
+
+    // A wrong key and a tampered file look the same to the cipher
+    key.cipher().decrypt(Nonce::from_slice(nonce), sealed)
+        .map_err(|_| io::Error::new(ErrorKind::InvalidData, "Failed to decrypt input, wrong key or corrupt file"))
+}
+
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(EXTENSION);
+    PathBuf::from(name)
+}
+
+pub fn is_encrypted(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == EXTENSION)
+}
+
+// Decrypts an encrypted input with the key from `Key::find`. `source::open` falls back to
+// `input.txt.enc` for a missing `input.txt`, so days don't need to know how it's stored.
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    read_with_key(path, Key::find)
+}
+
+fn read_with_key<F: FnOnce() -> io::Result<Key>>(path: &Path, key: F) -> io::Result<Vec<u8>> {
+    let data = fs::read(path)?;
+    decrypt(&key()?, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let key = Key::generate();
+        let sealed = encrypt(&key, b"1721\n979\n");
+        assert!(sealed.starts_with(HEADER));
+        assert_ne!(sealed, encrypt(&key, b"1721\n979\n"));
+        assert_eq!(b"1721\n979\n".to_vec(), decrypt(&key, &sealed).unwrap());
+
+        assert!(decrypt(&Key::generate(), &sealed).is_err());
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&key, &tampered).is_err());
+        assert!(decrypt(&key, b"1721\n979\n").is_err());
+    }
+
+    #[test]
+    fn test_key_hex() {
+        let key = Key::generate();
+        assert_eq!(key.to_hex(), Key::from_hex(&format!("{}\n", key.to_hex())).unwrap().to_hex());
+        assert!(Key::from_hex("abc").is_err());
+        assert!(Key::from_hex(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn test_read() {
+        let dir = env::temp_dir().join(format!("aoc-vault-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let key = Key::generate();
+        let input = encrypted_path(&dir.join("input.txt"));
+        fs::write(&input, encrypt(&key, b"F10\n")).unwrap();
+
+        let read = read_with_key(&input, || Key::from_hex(&key.to_hex()));
+        let wrong_key = read_with_key(&input, || Ok(Key::generate()));
+        let missing = read_with_key(&dir.join("missing.txt.enc"), || panic!("No key needed"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(b"F10\n".to_vec(), read.unwrap());
+        assert_eq!(ErrorKind::InvalidData, wrong_key.unwrap_err().kind());
+        assert_eq!(ErrorKind::NotFound, missing.unwrap_err().kind());
+    }
+}
@@ -0,0 +1,557 @@
+//! Day 1: Report Repair. Finds the entries of an expense report that sum to 2020.
+
+use common::Puzzle;
+use common::generate::Rng;
+use common::lint::Schema;
+use common::variants::Variant;
+use num_bigint::BigInt;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// The sum the puzzle looks for
+pub const TARGET: i64 = 2020;
+
+/// The expense report, one entry per line
+pub fn parse(input: &str) -> Vec<i64> {
+    input.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.trim().parse().expect("Bad entry"))
+        .collect()
+}
+
+/// Product of the two entries that sum to 2020
+pub fn solve_a(input: &str) -> Option<BigInt> {
+    find(input, TARGET, 2).and_then(|c| c.product.exact().cloned())
+}
+
+/// Product of the three entries that sum to 2020
+pub fn solve_b(input: &str) -> Option<BigInt> {
+    find(input, TARGET, 3).and_then(|c| c.product.exact().cloned())
+}
+
+/// The first `k` entries found that sum to `target`, with their exact product
+pub fn find(input: &str, target: i64, k: usize) -> Option<Combination> {
+    ExpenseReport { values: parse(input), precision: Precision::Big, ..ExpenseReport::new(target, k) }.lookup()
+}
+
+/// Every set of `k` entries that sums to `target`. Entries are told apart by position, so
+/// repeated values give separate combinations but no entry is used twice in one.
+pub fn find_all(input: &str, target: i64, k: usize) -> Vec<Combination> {
+    ExpenseReport { values: parse(input), precision: Precision::Big, ..ExpenseReport::new(target, k) }.all()
+}
+
+/// True when the combinations don't agree on a product, so the answer depends on which is found
+pub fn is_ambiguous(combinations: &[Combination]) -> bool {
+    combinations.windows(2).any(|w| w[0].product != w[1].product)
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().line_pattern(r"-?\d+")
+}
+
+/// One pair and one triple summing to 2020. Every other entry is over 1010, so no two of them can
+/// be part of the same answer, and entries that would complete a different answer are skipped.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    loop {
+        let a = rng.range(1011, 1999);
+        let x = rng.range(2, 1009);
+        let y = rng.range((1011 - x).max(2), (2018 - x).min(1009));
+        let planted = vec![a, 2020 - a, x, y, 2020 - x - y];
+
+        let mut pairs = 0;
+        let mut triples = 0;
+        for i in 0..planted.len() {
+            for j in (i + 1)..planted.len() {
+                if planted[i] + planted[j] == 2020 {
+                    pairs += 1;
+                }
+                for k in (j + 1)..planted.len() {
+                    if planted[i] + planted[j] + planted[k] == 2020 {
+                        triples += 1;
+                    }
+                }
+            }
+        }
+        if pairs != 1 || triples != 1 {
+            continue;
+        }
+
+        let small = &planted[1..];
+        let mut forbidden: HashSet<i64> = small.iter().map(|s| 2020 - s).collect();
+        for (i, s1) in small.iter().enumerate() {
+            for s2 in &small[i + 1..] {
+                forbidden.insert(2020 - s1 - s2);
+            }
+        }
+
+        let mut values = planted;
+        while values.len() < size.max(5) {
+            let filler = rng.range(1011, 2019);
+            if !forbidden.contains(&filler) {
+                values.push(filler);
+            }
+        }
+        rng.shuffle(&mut values);
+
+        return values.iter().map(|v| format!("{}\n", v)).collect();
+    }
+}
+
+/// Entries of the report that sum to the target, in report order
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct Combination {
+    pub entries: Vec<i64>,
+    /// Where each entry is in the report, counting from 0
+    pub indices: Vec<usize>,
+    pub product: Product,
+}
+
+// Lines count from 1, as in an editor
+impl fmt::Display for Combination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self.entries.iter().map(|e| e.to_string()).collect();
+        let lines: Vec<String> = self.indices.iter().map(|i| (i + 1).to_string()).collect();
+        write!(f, "{} (lines {}): product {}", entries.join(" + "), lines.join(", "), self.product)
+    }
+}
+
+/// How wide products are computed. `Big` is arbitrary precision and never overflows.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum Precision {
+    #[default]
+    I64,
+    I128,
+    Big,
+}
+
+impl FromStr for Precision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "i64" => Ok(Precision::I64),
+            "i128" => Ok(Precision::I128),
+            "big" => Ok(Precision::Big),
+            _ => Err(format!("Unknown precision '{}', expected i64, i128 or big", s)),
+        }
+    }
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Precision::I64 => "i64",
+            Precision::I128 => "i128",
+            Precision::Big => "big",
+        })
+    }
+}
+
+/// The product of a combination's entries, checked rather than left to wrap
+#[derive(Clone, PartialEq, Debug)]
+pub enum Product {
+    Exact(BigInt),
+    /// Too big for the precision it was computed in
+    Overflow(Precision),
+}
+
+impl Product {
+    pub fn of(entries: &[i64], precision: Precision) -> Product {
+        let exact = match precision {
+            Precision::I64 => entries.iter().try_fold(1i64, |p, e| p.checked_mul(*e)).map(BigInt::from),
+            Precision::I128 => entries.iter().try_fold(1i128, |p, e| p.checked_mul(*e as i128)).map(BigInt::from),
+            Precision::Big => Some(entries.iter().map(|e| BigInt::from(*e)).product()),
+        };
+        exact.map_or(Product::Overflow(precision), Product::Exact)
+    }
+
+    pub fn exact(&self) -> Option<&BigInt> {
+        match self {
+            Product::Exact(product) => Some(product),
+            Product::Overflow(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Product {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Product::Exact(product) => write!(f, "{}", product),
+            Product::Overflow(precision) => write!(f, "overflows {}", precision),
+        }
+    }
+}
+
+// As a string, since JSON numbers can't hold every product
+#[cfg(feature = "serde")]
+impl serde::Serialize for Product {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// Lookup is a hash map for pairs and a sorted two-pointer scan under the other entries for
+// anything bigger
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default, Clone, Copy)]
+enum Strategy {
+    #[default]
+    Nested,
+    Lookup,
+}
+
+/// Finds `k` entries that sum to `target`, as a `Puzzle` with a variant per strategy
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExpenseReport {
+    values: Vec<i64>,
+    target: i64,
+    k: usize,
+    strategy: Strategy,
+    precision: Precision,
+    found: Option<Combination>,
+}
+
+impl ExpenseReport {
+    pub fn new(target: i64, k: usize) -> ExpenseReport {
+        assert!(k > 0, "Need at least one entry to sum");
+        ExpenseReport { values: Vec::new(), target, k, strategy: Strategy::Lookup, precision: Precision::I64, found: None }
+    }
+
+    pub fn precision(self, precision: Precision) -> ExpenseReport {
+        ExpenseReport { precision, ..self }
+    }
+
+    /// Part A: two entries summing to 2020
+    pub fn pair() -> ExpenseReport {
+        ExpenseReport::new(TARGET, 2)
+    }
+
+    /// Part B: three entries summing to 2020
+    pub fn triple() -> ExpenseReport {
+        ExpenseReport::new(TARGET, 3)
+    }
+
+    pub fn pair_variants() -> Vec<Variant<ExpenseReport>> {
+        vec![
+            Variant { name: "nested", puzzle: || ExpenseReport { strategy: Strategy::Nested, ..ExpenseReport::pair() } },
+            Variant { name: "hashed", puzzle: || ExpenseReport { strategy: Strategy::Lookup, ..ExpenseReport::pair() } },
+        ]
+    }
+
+    pub fn triple_variants() -> Vec<Variant<ExpenseReport>> {
+        vec![
+            Variant { name: "nested", puzzle: || ExpenseReport { strategy: Strategy::Nested, ..ExpenseReport::triple() } },
+            Variant { name: "sorted", puzzle: || ExpenseReport { strategy: Strategy::Lookup, ..ExpenseReport::triple() } },
+        ]
+    }
+
+    pub fn found(&self) -> Option<&Combination> {
+        self.found.as_ref()
+    }
+
+    fn combination(&self, mut indices: Vec<usize>) -> Combination {
+        indices.sort_unstable();
+        let entries: Vec<i64> = indices.iter().map(|i| self.values[*i]).collect();
+        Combination { product: Product::of(&entries, self.precision), entries, indices }
+    }
+
+    // Sums are i128 throughout, which k entries of i64 can't overflow
+
+    // Every combination in report order, O(n^k)
+    fn nested(&self) -> Option<Combination> {
+        self.nested_from(0, self.target as i128, &mut Vec::with_capacity(self.k))
+    }
+
+    fn nested_from(&self, start: usize, remaining: i128, chosen: &mut Vec<usize>) -> Option<Combination> {
+        if chosen.len() == self.k {
+            return if remaining == 0 { Some(self.combination(chosen.clone())) } else { None };
+        }
+        for i in start..self.values.len() {
+            chosen.push(i);
+            if let Some(found) = self.nested_from(i + 1, remaining - self.values[i] as i128, chosen) {
+                return Some(found);
+            }
+            chosen.pop();
+        }
+        None
+    }
+
+    // Picks all but the last entry in position order, then looks up every later position holding
+    // the value that's left, O(n^(k-1)) plus the number found
+    fn all(&self) -> Vec<Combination> {
+        let mut positions: HashMap<i64, Vec<usize>> = HashMap::new();
+        for (i, value) in self.values.iter().enumerate() {
+            positions.entry(*value).or_default().push(i);
+        }
+        let mut found = Vec::new();
+        self.all_from(&positions, 0, self.target as i128, &mut Vec::with_capacity(self.k), &mut found);
+        found
+    }
+
+    fn all_from(&self, positions: &HashMap<i64, Vec<usize>>, start: usize, remaining: i128, chosen: &mut Vec<usize>,
+                found: &mut Vec<Combination>) {
+        if chosen.len() + 1 == self.k {
+            let last = i64::try_from(remaining).ok().and_then(|r| positions.get(&r));
+            for last in last.into_iter().flatten().filter(|i| **i >= start) {
+                let mut indices = chosen.clone();
+                indices.push(*last);
+                found.push(self.combination(indices));
+            }
+            return;
+        }
+        for i in start..self.values.len() {
+            chosen.push(i);
+            self.all_from(positions, i + 1, remaining - self.values[i] as i128, chosen, found);
+            chosen.pop();
+        }
+    }
+
+    // O(n) for pairs, O(n^(k-1)) otherwise
+    fn lookup(&self) -> Option<Combination> {
+        match self.k {
+            1 => self.values.iter().position(|v| *v == self.target).map(|i| self.combination(vec![i])),
+            2 => self.hashed(),
+            _ => {
+                let mut sorted: Vec<(i64, usize)> = self.values.iter().copied().zip(0..).collect();
+                sorted.sort_unstable();
+                self.sorted_from(&sorted, 0, self.target as i128, &mut Vec::with_capacity(self.k))
+            },
+        }
+    }
+
+    fn hashed(&self) -> Option<Combination> {
+        let mut seen = HashMap::new();
+        for (index_b, entry_b) in self.values.iter().enumerate() {
+            // A difference outside i64 can't be an entry
+            if let Some(index_a) = self.target.checked_sub(*entry_b).and_then(|a| seen.get(&a)) {
+                return Some(self.combination(vec![*index_a, index_b]));
+            }
+            seen.entry(*entry_b).or_insert(index_b);
+        }
+        None
+    }
+
+    // Fixes entries in sorted order until two are left, then closes in on those from both ends
+    fn sorted_from(&self, sorted: &[(i64, usize)], start: usize, remaining: i128, chosen: &mut Vec<usize>) -> Option<Combination> {
+        if self.k - chosen.len() > 2 {
+            for i in start..sorted.len() {
+                chosen.push(sorted[i].1);
+                if let Some(found) = self.sorted_from(sorted, i + 1, remaining - sorted[i].0 as i128, chosen) {
+                    return Some(found);
+                }
+                chosen.pop();
+            }
+            return None;
+        }
+
+        let (mut low, mut high) = (start, sorted.len().checked_sub(1)?);
+        while low < high {
+            match (sorted[low].0 as i128 + sorted[high].0 as i128).cmp(&remaining) {
+                Ordering::Less => low += 1,
+                Ordering::Greater => high -= 1,
+                Ordering::Equal => {
+                    let mut indices = chosen.clone();
+                    indices.extend(&[sorted[low].1, sorted[high].1]);
+                    return Some(self.combination(indices));
+                },
+            }
+        }
+        None
+    }
+}
+
+impl Puzzle for ExpenseReport {
+    type ParsedLine = i64;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        self.values.push(item);
+    }
+
+    fn final_result(&mut self) -> String {
+        self.found = match self.strategy {
+            Strategy::Nested => self.nested(),
+            Strategy::Lookup => self.lookup(),
+        };
+        match self.found.as_ref().map(|c| &c.product) {
+            None => "Not found".to_string(),
+            Some(Product::Exact(product)) => product.to_string(),
+            Some(Product::Overflow(precision)) => format!("Overflow: the product doesn't fit in {}, try --precision big", precision),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+    use common::variants;
+
+    #[test]
+    fn example() {
+        let input = "1721\n979\n366\n299\n675\n1456\n";
+        assert_eq!(Some(BigInt::from(514579)), solve_a(input));
+        assert_eq!(Some(BigInt::from(241861950)), solve_b(input));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 500);
+        assert!(schema().lint(&input).is_empty());
+        let values: Vec<i64> = input.lines().map(|l| l.parse().unwrap()).collect();
+        assert_eq!(500, values.len());
+
+        let mut a = ExpenseReport::pair();
+        let mut b = ExpenseReport::triple();
+        assert_ne!("Not found", a.run_with_input(input.clone()).result);
+        assert_ne!("Not found", b.run_with_input(input).result);
+    }
+
+    #[test]
+    fn combinations() {
+        let input = "1721\n979\n366\n299\n675\n1456\n";
+        assert_eq!(Some(Combination { entries: vec![1721, 299], indices: vec![0, 3], product: Product::Exact(514579.into()) }), find(input, TARGET, 2));
+        assert_eq!(Some(Combination { entries: vec![979, 366, 675], indices: vec![1, 2, 4], product: Product::Exact(241861950.into()) }), find(input, TARGET, 3));
+        assert_eq!(vec![0, 2, 3, 5], find(input, 1721 + 366 + 299 + 1456, 4).unwrap().indices);
+        assert_eq!(vec![5], find(input, 1456, 1).unwrap().indices);
+        assert_eq!(None, find(input, 1, 2));
+        assert_eq!(None, find("1010\n", TARGET, 2));
+        assert_eq!(vec![1, 2], find("5\n1010\n1010\n", TARGET, 2).unwrap().indices);
+        assert_eq!(None, find("", TARGET, 3));
+    }
+
+    #[test]
+    fn all_combinations() {
+        let input = "1721\n979\n366\n299\n675\n1456\n";
+        assert_eq!(vec![find(input, TARGET, 2).unwrap()], find_all(input, TARGET, 2));
+        assert!(!is_ambiguous(&find_all(input, TARGET, 3)));
+        assert_eq!("1721 + 299 (lines 1, 4): product 514579", find_all(input, TARGET, 2)[0].to_string());
+
+        // Repeated values pair up by position, never with themselves
+        let repeated: Vec<Vec<usize>> = find_all("1010\n1010\n5\n1010\n", TARGET, 2).into_iter().map(|c| c.indices).collect();
+        assert_eq!(vec![vec![0, 1], vec![0, 3], vec![1, 3]], repeated);
+        assert!(find_all("1010\n", TARGET, 2).is_empty());
+
+        let several = find_all("1000\n1020\n10\n2010\n", TARGET, 2);
+        assert_eq!(2, several.len());
+        assert!(is_ambiguous(&several));
+        assert!(!is_ambiguous(&find_all("1010\n1010\n1010\n", TARGET, 2)));
+    }
+
+    #[test]
+    fn all_counts_every_combination() {
+        let mut rng = Rng::new(12);
+        for _ in 0..100 {
+            let values: Vec<i64> = (0..rng.range(0, 10)).map(|_| rng.range(-5, 10)).collect();
+            let k = rng.range(1, 4) as usize;
+            let target = rng.range(-5, 20);
+            let input: String = values.iter().map(|v| format!("{}\n", v)).collect();
+
+            // Counts subsets of k positions by bitmask
+            let expected = (0u32..1 << values.len())
+                .filter(|m| m.count_ones() as usize == k)
+                .filter(|m| (0..values.len()).filter(|i| m & 1 << i != 0).map(|i| values[i]).sum::<i64>() == target)
+                .count();
+            let all = find_all(&input, target, k);
+            assert_eq!(expected, all.len(), "{:?} k={} target={}", values, k, target);
+            assert_eq!(all.first().map(|c| c.indices.clone()), report_nested(&values, target, k).map(|c| c.indices));
+        }
+    }
+    fn report_nested(values: &[i64], target: i64, k: usize) -> Option<Combination> {
+        ExpenseReport { values: values.to_vec(), ..ExpenseReport::new(target, k) }.nested()
+    }
+
+    #[test]
+    fn overflowing_products() {
+        // Three entries of around 3 billion multiply past i64, four of around 10^12 past i128
+        let mut rng = Rng::new(13);
+        for _ in 0..50 {
+            let entries: Vec<i64> = (0..3).map(|_| rng.range(3_000_000_000, 4_000_000_000)).collect();
+            let target: i64 = entries.iter().sum();
+            let input: String = entries.iter().chain(&[1, 2]).map(|v| format!("{}\n", v)).collect();
+            let exact: i128 = entries.iter().map(|e| *e as i128).product();
+
+            let report = |precision| {
+                let mut report = ExpenseReport::new(target, 3).precision(precision);
+                report.run_with_input(input.clone());
+                report.found.unwrap().product
+            };
+            assert_eq!(Product::Overflow(Precision::I64), report(Precision::I64));
+            assert_eq!(Product::Exact(exact.into()), report(Precision::I128));
+            assert_eq!(Product::Exact(exact.into()), report(Precision::Big));
+        }
+
+        let entries = [1_000_000_000_007i64, -1_000_000_000_039, 1_000_000_000_061, 1_000_000_000_093];
+        assert_eq!(Product::Overflow(Precision::I128), Product::of(&entries, Precision::I128));
+        let big = entries.iter().map(|e| BigInt::from(*e)).fold(BigInt::from(1), |p, e| p * e);
+        assert_eq!(Product::Exact(big.clone()), Product::of(&entries, Precision::Big));
+        assert!(big < BigInt::from(0));
+        assert_eq!("overflows i64", Product::of(&[i64::MAX, 2], Precision::I64).to_string());
+        assert_eq!(Product::Exact(0.into()), Product::of(&[i64::MIN, 0, -1], Precision::I64));
+
+        // Just past the square root of i64::MAX
+        let mut report = ExpenseReport::new(6_074_001_000, 2);
+        assert!(report.run_with_input(String::from("3037000500\n3037000500\n")).result.starts_with("Overflow"));
+        assert_eq!(Ok(Precision::I128), "i128".parse());
+        assert!("u8".parse::<Precision>().is_err());
+    }
+
+    #[test]
+    fn sums_past_i64() {
+        // Partial sums leave i64 on the way to a target inside it
+        let input = format!("{}\n{}\n{}\n-5\n", i64::MAX, i64::MAX, i64::MIN);
+        assert_eq!(vec![0, 2], find(&input, -1, 2).unwrap().indices);
+        assert_eq!(vec![0, 1, 2], find(&input, i64::MAX - 1, 3).unwrap().indices);
+        assert_eq!(None, find(&input, i64::MIN, 2));
+        assert_eq!(1, find_all(&input, i64::MAX - 1, 3).len());
+        assert_eq!(find(&input, -6, 2), report_nested(&parse(&input), -6, 2).map(|c| Combination { product: Product::of(&c.entries, Precision::Big), ..c }));
+    }
+
+    #[test]
+    fn strategies_agree() {
+        let mut rng = Rng::new(11);
+        for _ in 0..200 {
+            let values: Vec<i64> = (0..rng.range(0, 12)).map(|_| rng.range(-20, 40)).collect();
+            let k = rng.range(1, 4) as usize;
+            let target = rng.range(-30, 90);
+            let report = |strategy| ExpenseReport { values: values.clone(), strategy, ..ExpenseReport::new(target, k) };
+
+            let nested = report(Strategy::Nested).nested();
+            let lookup = report(Strategy::Lookup).lookup();
+            assert_eq!(nested.is_some(), lookup.is_some(), "{:?} k={} target={}", values, k, target);
+            if let Some(found) = lookup {
+                assert_eq!(target, found.entries.iter().sum::<i64>());
+                assert_eq!(k, found.indices.len());
+                assert!(found.indices.windows(2).all(|w| w[0] < w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn variants_agree() {
+        for seed in 0..5 {
+            let input = generate::generate(generate_input, seed, 300);
+            variants::assert_agree(&ExpenseReport::pair_variants(), &input);
+            variants::assert_agree(&ExpenseReport::triple_variants(), &input);
+        }
+
+        let example = "1721\n979\n366\n299\n675\n1456\n";
+        variants::assert_agree(&ExpenseReport::pair_variants(), example);
+        variants::assert_agree(&ExpenseReport::triple_variants(), example);
+
+        if let Some(input) = variants::real_input() {
+            variants::assert_agree(&ExpenseReport::pair_variants(), &input);
+            variants::assert_agree(&ExpenseReport::triple_variants(), &input);
+        }
+    }
+}
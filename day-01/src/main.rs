@@ -1,64 +1,54 @@
-use common::Puzzle;
+use common::{args, generate, lint, variants, Puzzle};
+use day_01::{find_all, generate_input, is_ambiguous, schema, ExpenseReport, Precision, TARGET};
 
-fn main() {
-    let mut a: Puzzle1 = Default::default();
-    a.run();
-
-    let mut b: Puzzle2 = Default::default();
-    b.run();
-}
-
-#[derive(Default)]
-struct Puzzle1 {
-    values: Vec<i64>,
-}
-
-impl Puzzle for Puzzle1 {
-    type ParsedLine = i64;
-
-    fn process_item(&mut self, item: Self::ParsedLine) {
-        self.values.push(item);
-    }
+#[global_allocator]
+static ALLOC: common::alloc::CountingAllocator = common::alloc::CountingAllocator;
 
-    fn final_result(&mut self) -> String {
-        for i in 0..(self.values.len() - 1) {
-            let entry_a = self.values[i];
-            for j in (i + 1)..self.values.len() {
-                let entry_b = self.values[j];
-                if entry_a + entry_b == 2020 {
-                    return (entry_a * entry_b).to_string();
+fn main() {
+    match args::command().as_deref() {
+        Some("lint") => lint::run(&schema()),
+        Some("generate") => generate::run(generate_input, 200),
+        Some("variants") => {
+            variants::run(&ExpenseReport::pair_variants());
+            variants::run(&ExpenseReport::triple_variants());
+        },
+        _ => {
+            // `--target N` and `--k K` look for a different sum, or only for K entries, and
+            // `--precision i64|i128|big` sets how wide products are computed
+            let precision: Precision = args::value("--precision").map_or(Precision::I64, |p| p.parse().unwrap_or_else(|e: String| usage_error(&e)));
+            let target = args::value("--target").map_or(TARGET, |t| t.parse().expect("Bad --target"));
+            let sizes = args::value("--k").map_or(vec![2, 3], |k| vec![entries(&k)]);
+            let input = if args::flag("--all") { Some(common::read_input()) } else { None };
+            for k in sizes {
+                match &input {
+                    Some(input) => list_all(input, target, k),
+                    None => { ExpenseReport::new(target, k).precision(precision).run(); },
                 }
             }
         }
-        "Not found".to_string()
     }
 }
 
-#[derive(Default)]
-struct Puzzle2 {
-    values: Vec<i64>,
+// `--all` lists every combination, with exact products, instead of solving with the first one found
+fn list_all(input: &str, target: i64, k: usize) {
+    let all = find_all(input, target, k);
+    let flag = if is_ambiguous(&all) { ", AMBIGUOUS: their products differ" } else { "" };
+    println!("{} combinations of {} entries sum to {}{}", all.len(), k, target, flag);
+    for combination in &all {
+        println!("  {}", combination);
+    }
+    println!();
 }
 
-impl Puzzle for Puzzle2 {
-    type ParsedLine = i64;
-
-    fn process_item(&mut self, item: Self::ParsedLine) {
-        self.values.push(item);
+// `--k` must be a count of at least one entry
+fn entries(k: &str) -> usize {
+    match k.parse() {
+        Ok(k) if k > 0 => k,
+        _ => usage_error(&format!("Bad --k {:?}, need at least one entry to sum", k)),
     }
+}
 
-    fn final_result(&mut self) -> String {
-        for i in 0..(self.values.len() - 2) {
-            let entry_a = self.values[i];
-            for j in (i + 1)..(self.values.len() - 1) {
-                let entry_b = self.values[j];
-                for k in (j + 1)..self.values.len() {
-                    let entry_c = self.values[k];
-                    if entry_a + entry_b + entry_c == 2020 {
-                        return (entry_a * entry_b * entry_c).to_string();
-                    }
-                }
-            }
-        }
-        "Not found".to_string()
-    }
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\nUsage: day-01 [--target N] [--k K] [--precision i64|i128|big] [--all]", message);
+    std::process::exit(2);
 }
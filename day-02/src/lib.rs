@@ -0,0 +1,545 @@
+//! Day 2: Password Philosophy. Checks passwords against the corporate policy they were
+//! created under.
+
+use common::{FilteredInputLine, ParallelPuzzle, Puzzle};
+use common::generate::Rng;
+use common::lint::Schema;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// One policy line per password. Panics on the first bad line, saying which and why.
+pub fn parse(input: &str) -> Vec<PasswordEntry> {
+    input.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| l.parse().unwrap_or_else(|e| panic!("Line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// Passwords whose letter count is within the policy's range
+pub fn solve_a(input: &str) -> usize {
+    count(input, &CountInRange)
+}
+
+/// Passwords with the policy's letter in exactly one of its two positions
+pub fn solve_b(input: &str) -> usize {
+    count(input, &OnePosition)
+}
+
+/// Passwords the policy allows
+pub fn count(input: &str, policy: &dyn PasswordPolicy) -> usize {
+    parse(input).iter().filter(|r| policy.allows(r)).count()
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().line_pattern(r"\d+-\d+ \S+: \S+")
+}
+
+/// Positions always fall inside the password, so every policy can check every line
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let letters: Vec<char> = "abcdefgh".chars().collect();
+    let mut input = String::new();
+    for _ in 0..size {
+        let min = rng.range(1, 10);
+        let max = rng.range(min, 20);
+        let letter = rng.pick(&letters);
+        let length = rng.range(max, max + 10);
+        let password: String = (0..length).map(|_| *rng.pick(&letters)).collect();
+        input.push_str(&format!("{}\n", PasswordEntry { a: min as usize, b: max as usize, letters: letter.to_string(), password }));
+    }
+    input
+}
+
+/// A policy line, `a-b letters: password`. What `a` and `b` mean depends on the policy, and
+/// `letters` is usually one letter but can be a set of them, any of which counts.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(PartialEq, Debug)]
+pub struct PasswordEntry {
+    pub a: usize,
+    pub b: usize,
+    pub letters: String,
+    pub password: String,
+}
+
+impl PasswordEntry {
+    pub fn is_letter(&self, c: char) -> bool {
+        self.letters.contains(c)
+    }
+
+    pub fn occurrences(&self) -> usize {
+        self.password.chars().filter(|c| self.is_letter(*c)).count()
+    }
+
+    /// Whether the character at a position, counting from 1, is one of the letters. Positions
+    /// outside the password hold no letter.
+    pub fn letter_at(&self, position: usize) -> bool {
+        position.checked_sub(1)
+            .and_then(|i| self.password.chars().nth(i))
+            .is_some_and(|c| self.is_letter(c))
+    }
+}
+
+impl fmt::Display for PasswordEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{} {}: {}", self.a, self.b, self.letters, self.password)
+    }
+}
+
+/// Why a policy line couldn't be read
+#[derive(PartialEq, Debug)]
+pub enum EntryError {
+    /// Not shaped like `a-b letters: password`
+    Shape(String),
+    /// The first or second number isn't a number
+    Number { which: &'static str, text: String },
+    /// Positions count from 1
+    Zero { which: &'static str },
+    /// The first number is bigger than the second
+    Backwards { a: usize, b: usize },
+    NoLetters,
+    NoPassword,
+}
+
+impl fmt::Display for EntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryError::Shape(line) => write!(f, "expected 'a-b letters: password', got '{}'", line),
+            EntryError::Number { which, text } => write!(f, "the {} number '{}' isn't a whole number", which, text),
+            EntryError::Zero { which } => write!(f, "the {} number is 0, but positions count from 1", which),
+            EntryError::Backwards { a, b } => write!(f, "the range {}-{} runs backwards", a, b),
+            EntryError::NoLetters => write!(f, "no letters before the ':'"),
+            EntryError::NoPassword => write!(f, "no password after the ':'"),
+        }
+    }
+}
+
+impl Error for EntryError {}
+
+// Letters and passwords are any non-space characters, counted by `char` rather than byte
+impl FromStr for PasswordEntry {
+    type Err = EntryError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let shape = || EntryError::Shape(line.to_string());
+        let (policy, password) = line.split_once(':').ok_or_else(shape)?;
+        let (range, letters) = policy.split_once(' ').ok_or_else(shape)?;
+        let (a, b) = range.split_once('-').ok_or_else(shape)?;
+
+        let number = |text: &str, which| match text.parse() {
+            Ok(0) => Err(EntryError::Zero { which }),
+            Ok(n) => Ok(n),
+            Err(_) => Err(EntryError::Number { which, text: text.to_string() }),
+        };
+        let (a, b) = (number(a, "first")?, number(b, "second")?);
+        if a > b {
+            return Err(EntryError::Backwards { a, b });
+        }
+
+        let letters = letters.trim();
+        if letters.is_empty() {
+            return Err(EntryError::NoLetters);
+        }
+        let password = password.trim();
+        if password.is_empty() {
+            return Err(EntryError::NoPassword);
+        }
+        if letters.contains(char::is_whitespace) || password.contains(char::is_whitespace) {
+            return Err(shape());
+        }
+        Ok(PasswordEntry { a, b, letters: letters.to_string(), password: password.to_string() })
+    }
+}
+
+/// A reading of a policy line. Implement this to check passwords against a new policy.
+pub trait PasswordPolicy: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn allows(&self, entry: &PasswordEntry) -> bool;
+
+    /// Why the policy doesn't allow an entry, for audits
+    fn reason(&self, _entry: &PasswordEntry) -> String {
+        format!("not allowed by the {} policy", self.name())
+    }
+}
+
+// e.g. "letter 'b' occurs 0 times"
+fn occurrences_text(entry: &PasswordEntry) -> String {
+    match entry.letters.chars().count() {
+        1 => format!("letter '{}' occurs {} times", entry.letters, entry.occurrences()),
+        _ => format!("letters '{}' occur {} times", entry.letters, entry.occurrences()),
+    }
+}
+
+fn positions_text(entry: &PasswordEntry) -> String {
+    if entry.letter_at(entry.a) {
+        format!("positions {} and {} both contain '{}'", entry.a, entry.b, entry.letters)
+    } else {
+        format!("neither position {} nor {} contains '{}'", entry.a, entry.b, entry.letters)
+    }
+}
+
+/// Part A: the letters occur between `a` and `b` times
+pub struct CountInRange;
+
+impl PasswordPolicy for CountInRange {
+    fn name(&self) -> &'static str {
+        "count"
+    }
+
+    fn allows(&self, entry: &PasswordEntry) -> bool {
+        (entry.a..=entry.b).contains(&entry.occurrences())
+    }
+
+    fn reason(&self, entry: &PasswordEntry) -> String {
+        format!("{}, need {}-{}", occurrences_text(entry), entry.a, entry.b)
+    }
+}
+
+/// Part B: exactly one of positions `a` and `b` holds a letter
+pub struct OnePosition;
+
+impl PasswordPolicy for OnePosition {
+    fn name(&self) -> &'static str {
+        "one-position"
+    }
+
+    fn allows(&self, entry: &PasswordEntry) -> bool {
+        entry.letter_at(entry.a) ^ entry.letter_at(entry.b)
+    }
+
+    fn reason(&self, entry: &PasswordEntry) -> String {
+        positions_text(entry)
+    }
+}
+
+/// At least one of positions `a` and `b` holds a letter
+pub struct AnyPosition;
+
+impl PasswordPolicy for AnyPosition {
+    fn name(&self) -> &'static str {
+        "any-position"
+    }
+
+    fn allows(&self, entry: &PasswordEntry) -> bool {
+        entry.letter_at(entry.a) || entry.letter_at(entry.b)
+    }
+
+    fn reason(&self, entry: &PasswordEntry) -> String {
+        positions_text(entry)
+    }
+}
+
+/// The letters occur exactly `a` times, and `b` is ignored
+pub struct ExactCount;
+
+impl PasswordPolicy for ExactCount {
+    fn name(&self) -> &'static str {
+        "exact-count"
+    }
+
+    fn allows(&self, entry: &PasswordEntry) -> bool {
+        entry.occurrences() == entry.a
+    }
+
+    fn reason(&self, entry: &PasswordEntry) -> String {
+        format!("{}, need exactly {}", occurrences_text(entry), entry.a)
+    }
+}
+
+/// Every built-in policy, in the order they're reported
+pub fn policies() -> Vec<Box<dyn PasswordPolicy>> {
+    vec![Box::new(CountInRange), Box::new(OnePosition), Box::new(AnyPosition), Box::new(ExactCount)]
+}
+
+/// An entry a policy doesn't allow. Lines that can't be read are rejected by a `parse` policy.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(PartialEq, Debug)]
+pub struct Rejection {
+    /// Counting from 1
+    pub line: usize,
+    pub entry: String,
+    pub policy: &'static str,
+    pub reason: String,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}: {} ({})", self.line, self.policy, self.reason, self.entry)
+    }
+}
+
+/// Every rejection of every entry by the given policies, in line order
+pub fn audit(input: &str, policies: &[Box<dyn PasswordPolicy>]) -> Vec<Rejection> {
+    let mut rejections = Vec::new();
+    for (i, line) in input.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let reject = |policy, reason| Rejection { line: i + 1, entry: line.trim().to_string(), policy, reason };
+        match line.parse::<PasswordEntry>() {
+            Ok(entry) => rejections.extend(policies.iter()
+                .filter(|p| !p.allows(&entry))
+                .map(|p| reject(p.name(), p.reason(&entry)))),
+            Err(e) => rejections.push(reject("parse", e.to_string())),
+        }
+    }
+    rejections
+}
+
+/// Counts the passwords each policy allows in one pass, as a `ParallelPuzzle`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Passwords {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    policies: Vec<Box<dyn PasswordPolicy>>,
+    valid: Vec<usize>,
+    checked: usize,
+    // Lines are filtered in order before they're checked, so bad entries can say where they are
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lines_read: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pending_lines: VecDeque<usize>,
+}
+
+impl Passwords {
+    pub fn new(policies: Vec<Box<dyn PasswordPolicy>>) -> Passwords {
+        Passwords { valid: vec![0; policies.len()], policies, checked: 0, lines_read: 0, pending_lines: VecDeque::new() }
+    }
+
+    pub fn part_a() -> Passwords {
+        Passwords::new(vec![Box::new(CountInRange)])
+    }
+
+    pub fn part_b() -> Passwords {
+        Passwords::new(vec![Box::new(OnePosition)])
+    }
+
+    /// Every built-in policy
+    pub fn all() -> Passwords {
+        Passwords::new(policies())
+    }
+
+    /// Each policy's name and how many passwords it allowed
+    pub fn counts(&self) -> Vec<(&'static str, usize)> {
+        self.policies.iter().map(|p| p.name()).zip(self.valid.iter().copied()).collect()
+    }
+
+    pub fn checked(&self) -> usize {
+        self.checked
+    }
+}
+
+impl Puzzle for Passwords {
+    type ParsedLine = String;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        let allowed = self.map_item(item);
+        self.reduce_item(allowed);
+    }
+
+    fn filter_line(&mut self, line: &str) -> FilteredInputLine {
+        self.lines_read += 1;
+        let filtered = self.default_filter_line(line);
+        if let FilteredInputLine::Process = filtered {
+            self.pending_lines.push_back(self.lines_read);
+        }
+        filtered
+    }
+
+    // Just the count for a single policy, so each part's answer reads as before
+    fn final_result(&mut self) -> String {
+        match self.valid.as_slice() {
+            [valid] => valid.to_string(),
+            _ => self.counts().iter().map(|(name, valid)| format!("{}: {}", name, valid)).collect::<Vec<String>>().join(", "),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+impl ParallelPuzzle for Passwords {
+    // Whether each policy allows the password
+    type Mapped = Result<Vec<bool>, EntryError>;
+
+    fn map_item(&self, item: Self::ParsedLine) -> Self::Mapped {
+        let entry: PasswordEntry = item.parse()?;
+        Ok(self.policies.iter().map(|p| p.allows(&entry)).collect())
+    }
+
+    fn reduce_item(&mut self, allowed: Self::Mapped) {
+        let line = self.pending_lines.pop_front().unwrap_or(0);
+        let allowed = allowed.unwrap_or_else(|e| panic!("Line {}: {}", line, e));
+        for (valid, allowed) in self.valid.iter_mut().zip(allowed) {
+            if allowed {
+                *valid += 1;
+            }
+        }
+        self.checked += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+
+    fn allows(policy: &dyn PasswordPolicy, line: &str) -> bool {
+        policy.allows(&line.parse().unwrap())
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
+    fn example_1() {
+        assert_eq!(true, allows(&CountInRange, "1-3 a: abcde"));
+        assert_eq!(false, allows(&CountInRange, "1-3 b: cdefg"));
+        assert_eq!(true, allows(&CountInRange, "2-9 c: ccccccccc"));
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
+    fn example_2() {
+        assert_eq!(true, allows(&OnePosition, "1-3 a: abcde"));
+        assert_eq!(false, allows(&OnePosition, "1-3 b: cdefg"));
+        assert_eq!(false, allows(&OnePosition, "2-9 c: ccccccccc"));
+    }
+
+    #[test]
+    fn extra_policies() {
+        assert!(allows(&AnyPosition, "1-3 a: abcde"));
+        assert!(allows(&AnyPosition, "2-9 c: ccccccccc"));
+        assert!(!allows(&AnyPosition, "1-3 b: cdefg"));
+        assert!(!allows(&AnyPosition, "20-30 c: ccccccccc"));
+        assert!(allows(&ExactCount, "1-3 a: abcde"));
+        assert!(!allows(&ExactCount, "2-9 c: ccccccccc"));
+
+        // Any letter of a set counts
+        assert!(allows(&CountInRange, "3-3 abc: abcde"));
+        assert!(allows(&OnePosition, "1-2 ax: abcde"));
+        assert!(!allows(&OnePosition, "1-2 ab: abcde"));
+        assert!(allows(&ExactCount, "2-2 cd: cdefg"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |line: &str| line.parse::<PasswordEntry>().unwrap_err();
+        assert_eq!(EntryError::Shape(String::from("1-3 a abcde")), error("1-3 a abcde"));
+        assert_eq!(EntryError::Shape(String::from("13 a: abcde")), error("13 a: abcde"));
+        assert_eq!(EntryError::Shape(String::from("1-3 a: abc de")), error("1-3 a: abc de"));
+        assert_eq!(EntryError::Number { which: "second", text: String::from("x") }, error("1-x a: abcde"));
+        assert_eq!(EntryError::Number { which: "first", text: String::new() }, error("-1-3 a: abcde"));
+        assert_eq!(EntryError::Zero { which: "first" }, error("0-3 a: abcde"));
+        assert_eq!(EntryError::Backwards { a: 9, b: 2 }, error("9-2 c: ccccccccc"));
+        assert_eq!(EntryError::NoLetters, error("1-3 : abcde"));
+        assert_eq!(EntryError::NoPassword, error("1-3 a: "));
+        assert_eq!("the range 9-2 runs backwards", error("9-2 c: cc").to_string());
+        assert_eq!("the first number is 0, but positions count from 1", error("0-3 a: abcde").to_string());
+
+        let result = std::panic::catch_unwind(|| parse("1-3 a: abcde\n\n1-3 b cdefg\n"));
+        assert_eq!("Line 3: expected 'a-b letters: password', got '1-3 b cdefg'", result.unwrap_err().downcast_ref::<String>().unwrap());
+
+        // The puzzle says where, whether it runs in order or across threads
+        let bad = "1-3 a: abcde\n\n1-3 b cdefg\n2-9 c: ccccccccc\n";
+        let result = std::panic::catch_unwind(|| { Passwords::all().run_with_input(bad.to_string()); });
+        assert_eq!("Line 3: expected 'a-b letters: password', got '1-3 b cdefg'", result.unwrap_err().downcast_ref::<String>().unwrap());
+        let result = std::panic::catch_unwind(|| { Passwords::all().run_parallel_with_input(bad.to_string()); });
+        assert_eq!("Line 3: expected 'a-b letters: password', got '1-3 b cdefg'", result.unwrap_err().downcast_ref::<String>().unwrap());
+    }
+
+    #[test]
+    fn unicode() {
+        let entry: PasswordEntry = "2-3 ü: aüüb\r".parse().unwrap();
+        assert_eq!(PasswordEntry { a: 2, b: 3, letters: String::from("ü"), password: String::from("aüüb") }, entry);
+        assert!(CountInRange.allows(&entry));
+        assert!(!OnePosition.allows(&entry));
+        assert!(allows(&OnePosition, "1-4 ßé: ßaaa"));
+        assert!(allows(&AnyPosition, "3-9 名: 日本名"));
+        assert!(!allows(&AnyPosition, "4-9 名: 日本名"));
+    }
+
+    #[test]
+    fn audit_reasons() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n\n2-9 c: ccccccccc\n2-9 c ccccccccc\n";
+        let rejections = audit(input, &policies());
+        let lines: Vec<String> = rejections.iter().map(|r| r.to_string()).collect();
+        assert_eq!(vec![
+            "line 2: count: letter 'b' occurs 0 times, need 1-3 (1-3 b: cdefg)",
+            "line 2: one-position: neither position 1 nor 3 contains 'b' (1-3 b: cdefg)",
+            "line 2: any-position: neither position 1 nor 3 contains 'b' (1-3 b: cdefg)",
+            "line 2: exact-count: letter 'b' occurs 0 times, need exactly 1 (1-3 b: cdefg)",
+            "line 4: one-position: positions 2 and 9 both contain 'c' (2-9 c: ccccccccc)",
+            "line 4: exact-count: letter 'c' occurs 9 times, need exactly 2 (2-9 c: ccccccccc)",
+            "line 5: parse: expected 'a-b letters: password', got '2-9 c ccccccccc' (2-9 c ccccccccc)",
+        ], lines);
+        assert_eq!("letters 'ab' occur 3 times, need 1-2", CountInRange.reason(&"1-2 ab: abac".parse().unwrap()));
+
+        // Policies without their own reasons get a generic one
+        struct Never;
+        impl PasswordPolicy for Never {
+            fn name(&self) -> &'static str {
+                "never"
+            }
+            fn allows(&self, _entry: &PasswordEntry) -> bool {
+                false
+            }
+        }
+        assert_eq!("not allowed by the never policy", audit("1-3 a: abcde", &[Box::new(Never)])[0].reason);
+    }
+
+    #[test]
+    fn all_policies() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n";
+        let mut all = Passwords::all();
+        assert_eq!("count: 2, one-position: 1, any-position: 2, exact-count: 1", all.run_parallel_with_input(input.to_string()).result);
+        assert_eq!(3, all.checked());
+        assert_eq!("1", Passwords::part_b().run_with_input(input.to_string()).result);
+
+        // Policies from outside the crate go through the same record
+        struct Palindrome;
+        impl PasswordPolicy for Palindrome {
+            fn name(&self) -> &'static str {
+                "palindrome"
+            }
+            fn allows(&self, entry: &PasswordEntry) -> bool {
+                entry.password.chars().eq(entry.password.chars().rev())
+            }
+        }
+        let mut custom = Passwords::new(vec![Box::new(CountInRange), Box::new(Palindrome)]);
+        custom.run_parallel_with_input(input.to_string());
+        assert_eq!(vec![("count", 2), ("palindrome", 1)], custom.counts());
+    }
+
+    #[test]
+    fn solve() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n";
+        assert_eq!(2, solve_a(input));
+        assert_eq!(1, solve_b(input));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 1000);
+        assert!(schema().lint(&input).is_empty());
+
+        let mut all = Passwords::all();
+        all.run_parallel_with_input(input.clone());
+        assert_eq!(1000, all.checked());
+        assert_eq!(solve_a(&input), all.counts()[0].1);
+        assert_eq!(solve_b(&input), all.counts()[1].1);
+    }
+
+    #[test]
+    fn rule_round_trip() {
+        let letters: Vec<char> = ('a'..='z').chain("éüß名".chars()).collect();
+        generate::assert_round_trip(300, |rng| {
+            let a = rng.range(1, u32::MAX as i64) as usize;
+            let b = rng.range(a as i64, u32::MAX as i64) as usize;
+            let letters_length = rng.range(1, 4);
+            let set = (0..letters_length).map(|_| *rng.pick(&letters)).collect();
+            let length = rng.range(1, 40);
+            let password = (0..length).map(|_| *rng.pick(&letters)).collect();
+            PasswordEntry { a, b, letters: set, password }
+        }, |entry| entry.to_string(), |line| line.parse().unwrap());
+    }
+}
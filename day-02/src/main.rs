@@ -1,107 +1,48 @@
-use common::Puzzle;
-use text_io::scan;
+use common::{args, generate, lint, ParallelPuzzle};
+use day_02::{audit, generate_input, policies, schema, Passwords, Rejection};
+use std::iter;
 
-fn main() {
-    let mut a: Puzzle1 = Default::default();
-    a.run();
-
-    let mut b: Puzzle2 = Default::default();
-    b.run();
-}
-
-#[derive(Default)]
-struct Puzzle1 {
-    valid_passwords: i64,
-    invalid_passwords: i64,
-}
-
-impl Puzzle1 {
-    fn is_valid(rule: String) -> bool {
-        let min: usize;
-        let max: usize;
-        let letter: String;
-        let password: String;
-        let mut chars = rule.into_bytes().into_iter();
-        scan!(chars => "{}-{} {}: {}", min, max, letter, password);
-
-        let occurrences = password.matches(&letter).count();
-        (min..=max).contains(&occurrences)
-    }
-}
+#[global_allocator]
+static ALLOC: common::alloc::CountingAllocator = common::alloc::CountingAllocator;
 
-impl Puzzle for Puzzle1 {
-    type ParsedLine = String;
-
-    fn process_item(&mut self, item: Self::ParsedLine) {
-        if Puzzle1::is_valid(item) {
-            self.valid_passwords += 1;
-        } else {
-            self.invalid_passwords += 1;
+fn main() {
+    match args::command().as_deref() {
+        Some("lint") => lint::run(&schema()),
+        Some("generate") => generate::run(generate_input, 1000),
+        Some("audit") => print_audit(),
+        _ => {
+            // One pass reports a count for every policy
+            Passwords::all().run_parallel();
         }
     }
-
-    fn final_result(&mut self) -> String {
-        self.valid_passwords.to_string()
-    }
-}
-
-#[derive(Default)]
-struct Puzzle2 {
-    valid_passwords: i64,
-    invalid_passwords: i64,
 }
 
-impl Puzzle2 {
-    fn is_valid(rule: String) -> bool {
-        let pos_a: usize;
-        let pos_b: usize;
-        let letter: char;
-        let password: String;
-        let mut chars = rule.into_bytes().into_iter();
-        scan!(chars => "{}-{} {}: {}", pos_a, pos_b, letter, password);
-
-
-        let pos_a_char = password.chars().nth(pos_a - 1).expect("Pos A OOB");
-        let pos_b_char = password.chars().nth(pos_b - 1).expect("Pos B OOB");
-
-        let in_pos_a = letter == pos_a_char;
-        let in_pos_b = letter == pos_b_char;
-
-        in_pos_a ^ in_pos_b
+// `audit [--json]` lists every entry each policy rejects and why
+fn print_audit() {
+    let policies = policies();
+    let rejections = audit(&common::read_input(), &policies);
+    if args::flag("--json") {
+        return print_json(&rejections);
     }
-}
-
-impl Puzzle for Puzzle2 {
-    type ParsedLine = String;
 
-    fn process_item(&mut self, item: Self::ParsedLine) {
-        if Puzzle2::is_valid(item) {
-            self.valid_passwords += 1;
-        } else {
-            self.invalid_passwords += 1;
-        }
+    for rejection in &rejections {
+        println!("{}", rejection);
     }
-
-    fn final_result(&mut self) -> String {
-        self.valid_passwords.to_string()
+    let names = policies.iter().map(|p| p.name()).chain(iter::once("parse"));
+    for name in names {
+        let rejected = rejections.iter().filter(|r| r.policy == name).count();
+        if rejected > 0 || name != "parse" {
+            println!("{}: {} rejected", name, rejected);
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn example_1() {
-        assert_eq!(true, Puzzle1::is_valid("1-3 a: abcde".to_string()));
-        assert_eq!(false, Puzzle1::is_valid("1-3 b: cdefg".to_string()));
-        assert_eq!(true, Puzzle1::is_valid("2-9 c: ccccccccc".to_string()));
-    }
+#[cfg(feature = "serde")]
+fn print_json(rejections: &[Rejection]) {
+    println!("{}", common::snapshot::to_json(&rejections));
+}
 
-    #[test]
-    fn example_2() {
-        assert_eq!(true, Puzzle2::is_valid("1-3 a: abcde".to_string()));
-        assert_eq!(false, Puzzle2::is_valid("1-3 b: cdefg".to_string()));
-        assert_eq!(false, Puzzle2::is_valid("2-9 c: ccccccccc".to_string()));
-    }
+#[cfg(not(feature = "serde"))]
+fn print_json(_rejections: &[Rejection]) {
+    panic!("JSON audits need the serde feature: cargo run --features serde -- audit --json");
 }
@@ -0,0 +1,761 @@
+//! Day 3: Toboggan Trajectory. Counts the trees hit sliding down a map that repeats to the
+//! right.
+
+use common::{FilteredInputLine, Puzzle};
+use common::generate::Rng;
+use common::lint::Schema;
+use std::cmp::max;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The map, one row of ground per line. Panics on a ragged map or an unknown character, saying
+/// where; `Puzzle1::load` returns the error instead.
+pub fn parse(input: &str) -> Vec<Line> {
+    Puzzle1::default().load(input).unwrap_or_else(|e| panic!("{}", e)).slope
+}
+
+/// Trees hit going right 3, down 1
+pub fn solve_a(input: &str) -> i64 {
+    Puzzle1::from_lines(parse(input)).count_trees(3, 1)
+}
+
+/// Product of the trees hit on each of the five slopes
+pub fn solve_b(input: &str) -> i64 {
+    Puzzle1::from_lines(parse(input)).product_of_slopes(&PART_B_SLOPES)
+}
+
+/// The slope part A takes
+pub const PART_A_SLOPE: Slope = Slope { right: 3, down: 1 };
+
+/// The slopes part B multiplies together
+pub const PART_B_SLOPES: [Slope; 5] = [
+    Slope { right: 1, down: 1 },
+    Slope { right: 3, down: 1 },
+    Slope { right: 5, down: 1 },
+    Slope { right: 7, down: 1 },
+    Slope { right: 1, down: 2 },
+];
+
+/// Slopes separated by whitespace, e.g. `1,1 3,1` on the command line or one per line in a file
+pub fn parse_slopes(text: &str) -> Result<Vec<Slope>, String> {
+    text.split_whitespace().map(str::parse).collect()
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().allowed_chars("#.").consistent_width()
+}
+
+/// A valid input of roughly `size` lines for the `generate` command
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let line: String = (0..31).map(|_| if rng.chance(0.2) { '#' } else { '.' }).collect();
+        input.push_str(&line);
+        input.push('\n');
+    }
+    input
+}
+
+/// A cell of the map. Terrain other than open ground and trees comes from a `Legend`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum GroundState {
+    Open,
+    Tree,
+    Other(char),
+}
+
+impl GroundState {
+    pub fn symbol(self) -> char {
+        match self {
+            GroundState::Open => '.',
+            GroundState::Tree => '#',
+            GroundState::Other(symbol) => symbol,
+        }
+    }
+}
+
+pub type Line = Vec<GroundState>;
+
+/// A kind of terrain and what crossing it costs
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(PartialEq, Clone, Debug)]
+pub struct Terrain {
+    pub symbol: char,
+    pub name: String,
+    pub weight: u64,
+}
+
+/// The terrain a map can hold. Open ground `.` costs 0 and a tree `#` costs 1, so by default a
+/// slope's cost is the trees it hits.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(PartialEq, Clone, Debug)]
+pub struct Legend {
+    terrain: Vec<Terrain>,
+}
+
+impl Default for Legend {
+    fn default() -> Self {
+        Legend { terrain: vec![
+            Terrain { symbol: '.', name: String::from("open"), weight: 0 },
+            Terrain { symbol: '#', name: String::from("tree"), weight: 1 },
+        ] }
+    }
+}
+
+impl Legend {
+    /// Adds a kind of terrain, or replaces the one with the same symbol
+    pub fn declare(&mut self, terrain: Terrain) {
+        match self.terrain.iter_mut().find(|t| t.symbol == terrain.symbol) {
+            Some(existing) => *existing = terrain,
+            None => self.terrain.push(terrain),
+        }
+    }
+
+    pub fn get(&self, symbol: char) -> Option<&Terrain> {
+        self.terrain.iter().find(|t| t.symbol == symbol)
+    }
+
+    pub fn weight(&self, state: GroundState) -> u64 {
+        self.get(state.symbol()).map_or(0, |t| t.weight)
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Legend::default()
+    }
+}
+
+// One terrain per line, `symbol name weight`, e.g. `~ ice 3`, added to the default legend
+impl FromStr for Legend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut legend = Legend::default();
+        for (i, line) in s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let bad = |why: &str| format!("Bad legend line {}: {} in '{}'", i + 1, why, line);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (symbol, name, weight) = match fields.as_slice() {
+                [symbol, name, weight] => (symbol, name, weight),
+                _ => return Err(bad("expected symbol, name and weight")),
+            };
+
+            let mut chars = symbol.chars();
+            let symbol = match (chars.next(), chars.next()) {
+                (Some(symbol), None) => symbol,
+                _ => return Err(bad("the symbol should be one character")),
+            };
+            let weight = weight.parse().map_err(|_| bad("the weight should be a whole number"))?;
+            legend.declare(Terrain { symbol, name: name.to_string(), weight });
+        }
+        Ok(legend)
+    }
+}
+
+/// What loading does with a row narrower or wider than the first
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default, PartialEq, Copy, Clone, Debug)]
+pub enum Ragged {
+    #[default]
+    Reject,
+    /// Fill out every row with open ground to the widest
+    Pad,
+}
+
+/// Where and why a map couldn't be loaded. Lines and columns count from 1.
+#[derive(PartialEq, Debug)]
+pub enum MapError {
+    Unknown { line: usize, column: usize, symbol: char },
+    Ragged { line: usize, width: usize, expected: usize },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::Unknown { line, column, symbol } =>
+                write!(f, "line {}, column {}: '{}' isn't in the legend", line, column, symbol),
+            MapError::Ragged { line, width, expected } =>
+                write!(f, "line {}, column {}: the row is {} wide but the first is {}", line, width.min(expected) + 1, width, expected),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+/// How far the toboggan goes right for each step down, written `right,down`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct Slope {
+    pub right: usize,
+    pub down: usize,
+}
+
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.right, self.down)
+    }
+}
+
+impl FromStr for Slope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (right, down) = s.split_once(',').ok_or_else(|| format!("Bad slope '{}', expected right,down", s))?;
+        let number = |n: &str| n.trim().parse::<usize>().map_err(|_| format!("Bad slope '{}', '{}' isn't a whole number", s, n));
+        let slope = Slope { right: number(right)?, down: number(down)? };
+        if slope.down == 0 {
+            return Err(format!("Bad slope '{}', it has to go down", s));
+        }
+        Ok(slope)
+    }
+}
+
+/// The slopes with the fewest and the most trees from `Puzzle1::search`, or the lowest and
+/// highest cost with a legend. Ties go to the first found, searching each `down` in turn from
+/// `right` 0.
+#[derive(PartialEq, Debug)]
+pub struct Search {
+    pub fewest: (Slope, i64),
+    pub most: (Slope, i64),
+    pub slopes: usize,
+}
+
+// Rows are bitsets of their trees, 64 columns to a word
+type Trees = Vec<u64>;
+
+fn to_trees(line: &[GroundState]) -> Trees {
+    let mut trees = vec![0; line.len().div_ceil(64)];
+    for (x, state) in line.iter().enumerate() {
+        if *state == GroundState::Tree {
+            trees[x / 64] |= 1 << (x % 64);
+        }
+    }
+    trees
+}
+
+/// Both parts as a `Puzzle`, or the trees on each of a set of slopes and their product
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+pub struct Puzzle1 {
+    slope: Vec<Line>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    trees: Vec<Trees>,
+    max_line: usize,
+    slopes: Option<Vec<Slope>>,
+    legend: Legend,
+    ragged: Ragged,
+    // Blank lines are skipped, so rows keep count of the lines read to say where they are
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lines_read: usize,
+}
+
+impl Puzzle1 {
+    /// Rows narrower than the widest are padded with open ground
+    pub fn from_lines(slope: Vec<Line>) -> Puzzle1 {
+        let max_line = slope.iter().map(|l| l.len()).max().unwrap_or(0);
+        let mut puzzle = Puzzle1 { slope, max_line, ..Default::default() };
+        puzzle.pad();
+        puzzle
+    }
+
+    /// Solves for these slopes instead of the two parts
+    pub fn with_slopes(slopes: Vec<Slope>) -> Puzzle1 {
+        Puzzle1 { slopes: Some(slopes), ..Default::default() }
+    }
+
+    /// Terrain beyond open ground and trees, and its weights
+    pub fn legend(self, legend: Legend) -> Puzzle1 {
+        Puzzle1 { legend, ..self }
+    }
+
+    pub fn ragged(self, ragged: Ragged) -> Puzzle1 {
+        Puzzle1 { ragged, ..self }
+    }
+
+    /// Reads a whole map, with this puzzle's legend and ragged row policy
+    pub fn load(mut self, input: &str) -> Result<Puzzle1, MapError> {
+        for (i, line) in input.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            self.push(i + 1, line)?;
+        }
+        self.pad();
+        Ok(self)
+    }
+
+    // `row` is the line of the input it came from, counting from 1
+    fn push(&mut self, row: usize, input: &str) -> Result<(), MapError> {
+        let line = input.chars().enumerate()
+            .map(|(i, c)| match c {
+                '.' => Ok(GroundState::Open),
+                '#' => Ok(GroundState::Tree),
+                c if self.legend.get(c).is_some() => Ok(GroundState::Other(c)),
+                c => Err(MapError::Unknown { line: row, column: i + 1, symbol: c }),
+            })
+            .collect::<Result<Line, MapError>>()?;
+
+        if let (Ragged::Reject, Some(first)) = (self.ragged, self.slope.first()) {
+            if line.len() != first.len() {
+                return Err(MapError::Ragged { line: row, width: line.len(), expected: first.len() });
+            }
+        }
+        self.max_line = max(self.max_line, line.len());
+        self.trees.push(to_trees(&line));
+        self.slope.push(line);
+        Ok(())
+    }
+
+    // Counting expects a bit for every column, so rows that grow get new bitsets
+    fn pad(&mut self) {
+        let width = self.max_line;
+        let mut padded = false;
+        for line in self.slope.iter_mut().filter(|l| l.len() < width) {
+            line.resize(width, GroundState::Open);
+            padded = true;
+        }
+        if padded || self.trees.len() != self.slope.len() {
+            self.trees = self.slope.iter().map(|l| to_trees(l)).collect();
+        }
+    }
+
+    pub fn count_trees(&self, x_step: usize, y_step: usize) -> i64 {
+        if self.max_line == 0 {
+            return 0;
+        }
+        let x_step = x_step % self.max_line;
+        let mut trees = 0;
+        let mut x = x_step;
+        let mut y = y_step;
+        while y < self.trees.len() {
+            if self.trees[y][x / 64] & 1 << (x % 64) != 0 {
+                trees += 1;
+            }
+            x = (x + x_step) % self.max_line;
+            y += y_step;
+        }
+
+        trees
+    }
+
+    /// The legend's weights summed over the cells a slope crosses, so the trees it hits unless
+    /// the legend says otherwise
+    pub fn cost(&self, x_step: usize, y_step: usize) -> u64 {
+        if self.max_line == 0 {
+            return 0;
+        }
+        let x_step = x_step % self.max_line;
+        let mut cost = 0;
+        let mut x = x_step;
+        let mut y = y_step;
+        while y < self.slope.len() {
+            cost += self.legend.weight(self.slope[y][x]);
+            x = (x + x_step) % self.max_line;
+            y += y_step;
+        }
+
+        cost
+    }
+
+    // Trees from the bitsets when that's all the cost is
+    fn score(&self, slope: Slope) -> i64 {
+        if self.legend.is_default() {
+            self.count_trees(slope.right, slope.down)
+        } else {
+            self.cost(slope.right, slope.down) as i64
+        }
+    }
+
+    fn product_of_slopes(&self, slopes: &[Slope]) -> i64 {
+        slopes.iter().map(|s| self.count_trees(s.right, s.down)).product()
+    }
+
+    /// Every slope going right 0 to `max_right` and down 1 to `max_down`. Going right by the
+    /// map's width or more lands where a smaller step would, and ties go to the first found, so
+    /// only steps narrower than the map are counted however wide the search.
+    pub fn search(&self, max_right: usize, max_down: usize) -> Option<Search> {
+        let distinct = max_right.min(self.max_line.max(1) - 1);
+        let mut found: Option<Search> = None;
+        for down in 1..=max_down {
+            for right in 0..=distinct {
+                let slope = Slope { right, down };
+                let trees = self.score(slope);
+
+                let search = found.get_or_insert(Search { fewest: (slope, trees), most: (slope, trees), slopes: 0 });
+                if trees < search.fewest.1 {
+                    search.fewest = (slope, trees);
+                }
+                if trees > search.most.1 {
+                    search.most = (slope, trees);
+                }
+            }
+        }
+        found.map(|search| Search { slopes: (max_right + 1) * max_down, ..search })
+    }
+
+    // e.g. "7 trees", or "7 trees, cost 12" with a legend
+    fn describe(&self, slope: Slope) -> String {
+        let trees = self.count_trees(slope.right, slope.down);
+        if self.legend.is_default() {
+            format!("{} trees", trees)
+        } else {
+            format!("{} trees, cost {}", trees, self.cost(slope.right, slope.down))
+        }
+    }
+
+    /// The map with the path down each slope drawn over it
+    pub fn paths(&self, slopes: &[Slope]) -> PathMap<'_> {
+        let mut visited = HashMap::new();
+        let mut widest = 0;
+        for (index, slope) in slopes.iter().enumerate() {
+            let (mut x, mut y) = (slope.right, slope.down);
+            while y < self.slope.len() {
+                visited.entry((x, y)).or_insert(index);
+                widest = widest.max(x);
+                x += slope.right;
+                y += slope.down;
+            }
+        }
+        PathMap { map: self, slopes: slopes.to_vec(), visited, tiles: widest / self.max_line.max(1) + 1 }
+    }
+}
+
+/// A cell of a `PathMap`. Paths are numbered by their slope's place in the list.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Cell {
+    Open,
+    Tree,
+    Clear(usize),
+    Hit(usize),
+}
+
+/// The map repeated to the right as far as the paths down it go, with each path's cells marked
+pub struct PathMap<'a> {
+    map: &'a Puzzle1,
+    slopes: Vec<Slope>,
+    visited: HashMap<(usize, usize), usize>,
+    tiles: usize,
+}
+
+// Per path, cycling when there are more paths than colours
+const ANSI_COLOURS: [u8; 6] = [33, 36, 35, 34, 32, 31];
+const RGB_COLOURS: [[u8; 3]; 6] = [[230, 170, 0], [0, 170, 210], [200, 60, 200], [50, 80, 220], [120, 200, 40], [220, 40, 40]];
+
+impl PathMap<'_> {
+    pub fn width(&self) -> usize {
+        self.tiles * self.map.max_line
+    }
+
+    pub fn height(&self) -> usize {
+        self.map.slope.len()
+    }
+
+    /// How many copies of the map it takes to fit the paths
+    pub fn tiles(&self) -> usize {
+        self.tiles
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> Cell {
+        let obstacle = self.map.legend.weight(self.map.slope[y][x % self.map.max_line]) > 0;
+        match (self.visited.get(&(x, y)), obstacle) {
+            (Some(path), true) => Cell::Hit(*path),
+            (Some(path), false) => Cell::Clear(*path),
+            (None, true) => Cell::Tree,
+            (None, false) => Cell::Open,
+        }
+    }
+
+    /// `X` where a path hits a tree, or any terrain with a weight, and `O` where it's clear.
+    /// Copies of the map are split by `|`, or with `ansi` every other copy is dimmed and each
+    /// path has its own colour.
+    pub fn to_text(&self, ansi: bool) -> String {
+        let mut text = String::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let tile = x / self.map.max_line;
+                if x % self.map.max_line == 0 && tile > 0 && !ansi {
+                    text.push('|');
+                }
+                let symbol = self.map.slope[y][x % self.map.max_line].symbol();
+                let (c, path) = match self.cell(x, y) {
+                    Cell::Open | Cell::Tree => (symbol, None),
+                    Cell::Clear(path) => ('O', Some(path)),
+                    Cell::Hit(path) => ('X', Some(path)),
+                };
+                match (ansi, path) {
+                    (false, _) => text.push(c),
+                    (true, Some(path)) => text.push_str(&format!("\x1b[1;{}m{}\x1b[0m", ANSI_COLOURS[path % ANSI_COLOURS.len()], c)),
+                    (true, None) if tile % 2 == 1 => text.push_str(&format!("\x1b[2m{}\x1b[0m", c)),
+                    (true, None) => text.push(c),
+                }
+            }
+            text.push('\n');
+        }
+
+        for (index, slope) in self.slopes.iter().enumerate() {
+            let key = if ansi { format!("\x1b[1;{}mX\x1b[0m", ANSI_COLOURS[index % ANSI_COLOURS.len()]) } else { String::from("X") };
+            text.push_str(&format!("{} {}: {}\n", key, slope, self.map.describe(*slope)));
+        }
+        text
+    }
+
+    /// RGB bytes, `scale` pixels to a cell. Every other copy of the map is shaded, paths are in
+    /// their colour and hits in a darker shade of it.
+    pub fn to_rgb(&self, scale: usize) -> (usize, usize, Vec<u8>) {
+        let (width, height) = (self.width() * scale, self.height() * scale);
+        let mut rgb = Vec::with_capacity(width * height * 3);
+        for py in 0..height {
+            for px in 0..width {
+                let (x, y) = (px / scale, py / scale);
+                let shaded = (x / self.map.max_line) % 2 == 1;
+                let colour = match self.cell(x, y) {
+                    Cell::Open if shaded => [215, 215, 225],
+                    Cell::Open => [240, 240, 240],
+                    Cell::Tree if shaded => [30, 95, 30],
+                    Cell::Tree => [40, 120, 40],
+                    Cell::Clear(path) => RGB_COLOURS[path % RGB_COLOURS.len()],
+                    Cell::Hit(path) => RGB_COLOURS[path % RGB_COLOURS.len()].map(|c| c / 2),
+                };
+                rgb.extend_from_slice(&colour);
+            }
+        }
+        (width, height, rgb)
+    }
+
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let (width, height, rgb) = self.to_rgb(scale);
+        let mut ppm = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        ppm.extend(rgb);
+        ppm
+    }
+
+    pub fn to_png(&self, scale: usize) -> io::Result<Vec<u8>> {
+        let (width, height, rgb) = self.to_rgb(scale);
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()
+            .and_then(|mut writer| writer.write_image_data(&rgb))
+            .map_err(io::Error::other)?;
+        Ok(png)
+    }
+
+    /// A PNG for a `.png` path, otherwise a PPM
+    pub fn write_image(&self, path: &Path, scale: usize) -> io::Result<()> {
+        let image = match path.extension() {
+            Some(e) if e == "png" => self.to_png(scale)?,
+            _ => self.to_ppm(scale),
+        };
+        fs::write(path, image)
+    }
+}
+
+impl Puzzle for Puzzle1 {
+    type ParsedLine = String;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        self.push(self.lines_read, &item).unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    fn filter_line(&mut self, line: &str) -> FilteredInputLine {
+        self.lines_read += 1;
+        self.default_filter_line(line)
+    }
+
+    // With a legend, each slope's cost is given alongside its trees
+    fn final_result(&mut self) -> String {
+        self.pad();
+        if self.slopes.is_none() && self.legend.is_default() {
+            return format!("{}, {}", self.count_trees(PART_A_SLOPE.right, PART_A_SLOPE.down), self.product_of_slopes(&PART_B_SLOPES));
+        }
+
+        let slopes = self.slopes.clone().unwrap_or_else(|| PART_B_SLOPES.to_vec());
+        let counts: Vec<String> = slopes.iter().map(|s| format!("{}: {}", s, self.describe(*s))).collect();
+        format!("{}; product: {}", counts.join("; "), self.product_of_slopes(&slopes))
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+
+    #[test]
+    fn example() {
+        assert_eq!(7, solve_a(EXAMPLE));
+        assert_eq!(336, solve_b(EXAMPLE));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 1000);
+        assert!(schema().lint(&input).is_empty());
+
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+        assert_eq!(1000, subject.slope.len());
+        assert!(subject.count_trees(3, 1) > 0);
+    }
+
+    const EXAMPLE: &str = "..##.......\n#...#...#..\n.#....#..#.\n..#.#...#.#\n.#...##..#.\n..#.##.....\n.#.#.#....#\n.#........#\n#.##...#...\n#...##....#\n.#..#...#.#\n";
+
+    #[test]
+    fn slopes() {
+        assert_eq!(Ok(PART_B_SLOPES.to_vec()), parse_slopes("1,1 3,1\n5,1\n 7,1 1,2\n"));
+        assert!(parse_slopes("3,0").unwrap_err().contains("has to go down"));
+        assert!(parse_slopes("3").is_err());
+        assert!(parse_slopes("3,x").is_err());
+        assert_eq!(Ok(Vec::new()), parse_slopes(""));
+
+        let mut subject = Puzzle1::with_slopes(parse_slopes("3,1 1,2").unwrap());
+        assert_eq!("3,1: 7 trees; 1,2: 2 trees; product: 14", subject.run_with_input(EXAMPLE.to_string()).result);
+    }
+
+    #[test]
+    fn wide_steps_wrap() {
+        let subject = Puzzle1::from_lines(parse(EXAMPLE));
+        assert_eq!(subject.count_trees(3, 1), subject.count_trees(3 + 11 * 1000, 1));
+        assert_eq!(0, Puzzle1::from_lines(Vec::new()).count_trees(3, 1));
+
+        // Wider than a word of the bitset
+        let wide: String = (0..5).map(|y| (0..100).map(|x| if x == 70 * y % 100 { '#' } else { '.' }).collect::<String>() + "\n").collect();
+        assert_eq!(4, Puzzle1::from_lines(parse(&wide)).count_trees(70, 1));
+    }
+
+    #[test]
+    fn paths() {
+        let subject = Puzzle1::from_lines(parse(EXAMPLE));
+        let paths = subject.paths(&[PART_A_SLOPE]);
+        assert_eq!(3, paths.tiles());
+        assert_eq!((33, 11), (paths.width(), paths.height()));
+
+        let text = paths.to_text(false);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!("..##.......|..##.......|..##.......", lines[0]);
+        assert_eq!("#..O#...#..|#...#...#..|#...#...#..", lines[1]);
+        assert_eq!(".#....X..#.|.#....#..#.|.#....#..#.", lines[2]);
+        assert_eq!(".#..#...#.#|.#..#...#.#|.#..#...X.#", lines[10]);
+        assert_eq!("X 3,1: 7 trees", lines[11]);
+        assert_eq!(7, text.matches('X').count() - 1);
+        assert_eq!(3, text.matches('O').count());
+
+        // Overlapping paths keep the first slope's mark
+        let same = subject.paths(&[Slope { right: 2, down: 1 }, Slope { right: 4, down: 2 }]);
+        assert_eq!(Cell::Clear(0), same.cell(4, 2));
+        let both = subject.paths(&[Slope { right: 2, down: 1 }, Slope { right: 1, down: 2 }]);
+        assert_eq!(Cell::Hit(1), both.cell(1, 2));
+        assert_eq!(Cell::Tree, both.cell(2, 0));
+        let ansi = both.to_text(true);
+        assert!(ansi.contains("\x1b[1;36mX\x1b[0m 1,2: 2 trees"));
+        assert!(ansi.starts_with("..##.......\x1b[2m.\x1b[0m"));
+    }
+
+    #[test]
+    fn images() {
+        let subject = Puzzle1::from_lines(parse(EXAMPLE));
+        let paths = subject.paths(&PART_B_SLOPES);
+        let ppm = paths.to_ppm(2);
+        let header = format!("P6\n{} {}\n255\n", paths.width() * 2, 22);
+        assert!(ppm.starts_with(header.as_bytes()));
+        assert_eq!(header.len() + paths.width() * 2 * 22 * 3, ppm.len());
+
+        let (_, _, rgb) = paths.to_rgb(1);
+        let pixel = |x: usize, y: usize| &rgb[(y * paths.width() + x) * 3..][..3];
+        assert_eq!([240, 240, 240], pixel(0, 0));
+        assert_eq!([40, 120, 40], pixel(2, 0));
+        assert_eq!([30, 95, 30], pixel(13, 0));
+        assert_eq!(RGB_COLOURS[0], pixel(1, 1));
+
+        let png = paths.to_png(3).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut decoded).unwrap();
+        assert_eq!(paths.to_rgb(3).2, decoded);
+    }
+
+    #[test]
+    fn malformed_maps() {
+        let load = |input: &str| Puzzle1::default().load(input);
+        assert_eq!(MapError::Unknown { line: 2, column: 3, symbol: 'x' }, load("...\n#.x\n").err().unwrap());
+        assert_eq!(MapError::Ragged { line: 3, width: 2, expected: 3 }, load("...\n#..\n.#\n").err().unwrap());
+        assert_eq!(MapError::Ragged { line: 2, width: 4, expected: 3 }, load("...\n#...\n").err().unwrap());
+        assert_eq!("line 3, column 3: the row is 2 wide but the first is 3", load("...\n#..\n.#\n").err().unwrap().to_string());
+        assert_eq!("line 2, column 3: 'x' isn't in the legend", load("...\n#.x\n").err().unwrap().to_string());
+
+        let result = std::panic::catch_unwind(|| solve_a("...\n#.\n"));
+        assert_eq!("line 2, column 3: the row is 2 wide but the first is 3", result.unwrap_err().downcast_ref::<String>().unwrap());
+
+        // Rows are numbered by their line, blank ones included
+        assert_eq!(MapError::Ragged { line: 3, width: 2, expected: 3 }, load("...\n\n#.\n").err().unwrap());
+        assert_eq!(MapError::Unknown { line: 4, column: 2, symbol: 'x' }, load("\n...\n\n#x.\n").err().unwrap());
+        let result = std::panic::catch_unwind(|| { Puzzle1::default().run_with_input(String::from("...\n\n#.\n")); });
+        assert_eq!("line 3, column 3: the row is 2 wide but the first is 3", result.unwrap_err().downcast_ref::<String>().unwrap());
+
+        // Padded rows are open past their end
+        let padded = Puzzle1::default().ragged(Ragged::Pad).load("....\n.#\n...#\n#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#.#\n").unwrap();
+        assert_eq!(vec![GroundState::Open; 67], padded.slope[0]);
+        assert_eq!(1, padded.count_trees(1, 1));
+        assert_eq!(0, padded.count_trees(3, 1));
+        assert_eq!(1, padded.count_trees(66, 1));
+        let mut puzzle = Puzzle1::with_slopes(vec![Slope { right: 1, down: 1 }]).ragged(Ragged::Pad);
+        assert_eq!("1,1: 1 trees; product: 1", puzzle.run_with_input(String::from("....\n.#\n...#\n")).result);
+    }
+
+    #[test]
+    fn legend() {
+        let legend: Legend = "~ ice 3\n\n# tree 2\n".parse().unwrap();
+        assert_eq!(Some(3), legend.get('~').map(|t| t.weight));
+        assert_eq!(2, legend.weight(GroundState::Tree));
+        assert_eq!(0, legend.weight(GroundState::Open));
+        assert!(!legend.is_default());
+        assert!(Legend::default().is_default());
+        assert_eq!("Bad legend line 1: the symbol should be one character in '~~ ice 3'", "~~ ice 3".parse::<Legend>().unwrap_err());
+        assert!("~ ice".parse::<Legend>().unwrap_err().contains("expected symbol, name and weight"));
+        assert!("~ ice x".parse::<Legend>().unwrap_err().contains("whole number"));
+
+        let map = "..~.\n.~.#\n~.#.\n.#~.\n";
+        assert!(Puzzle1::default().load(map).is_err());
+        let iced = Puzzle1::default().legend(legend).load(map).unwrap();
+        assert_eq!(GroundState::Other('~'), iced.slope[1][1]);
+        assert_eq!(1, iced.count_trees(1, 1));
+        assert_eq!(3 + 2, iced.cost(1, 1));
+        assert_eq!(3 * 2, iced.cost(3, 1));
+        assert_eq!((Slope { right: 0, down: 1 }, 3), iced.search(3, 1).unwrap().fewest);
+        assert_eq!((Slope { right: 2, down: 1 }, 6), iced.search(3, 1).unwrap().most);
+
+        let text = iced.paths(&[Slope { right: 1, down: 1 }]).to_text(false);
+        assert_eq!("..~.\n.X.#\n~.X.\n.#~O\n", &text[..20]);
+        assert!(text.ends_with("X 1,1: 1 trees, cost 5\n"));
+
+        let mut puzzle = Puzzle1::default().legend("~ ice 3".parse().unwrap());
+        assert_eq!("1,1: 1 trees, cost 4; 3,1: 3 trees, cost 3; 5,1: 1 trees, cost 4; 7,1: 3 trees, cost 3; 1,2: 0 trees, cost 0; product: 0",
+                   puzzle.run_with_input(map.to_string()).result);
+    }
+
+    #[test]
+    fn search() {
+        let subject = Puzzle1::from_lines(parse(EXAMPLE));
+        let search = subject.search(1000, 3).unwrap();
+        assert_eq!(3 * 1001, search.slopes);
+
+        // Compared with counting every slope directly
+        let mut all: Vec<(Slope, i64)> = Vec::new();
+        for down in 1..=3 {
+            for right in 0..=1000 {
+                all.push((Slope { right, down }, subject.count_trees(right, down)));
+            }
+        }
+        assert_eq!(all.iter().map(|c| c.1).min(), Some(search.fewest.1));
+        assert_eq!(all.iter().map(|c| c.1).max(), Some(search.most.1));
+        assert_eq!(search.most, *all.iter().find(|c| c.1 == search.most.1).unwrap());
+        assert!(subject.search(5, 0).is_none());
+    }
+}
@@ -1,72 +1,77 @@
-use common::Puzzle;
-use std::cmp::max;
+use common::{args, generate, lint, Puzzle};
+use day_03::{generate_input, parse_slopes, schema, Legend, Puzzle1, Ragged, Slope, PART_A_SLOPE};
+use std::fs;
+use std::path::Path;
+
+#[global_allocator]
+static ALLOC: common::alloc::CountingAllocator = common::alloc::CountingAllocator;
 
 fn main() {
-    let mut a: Puzzle1 = Default::default();
-    a.run();
+    match args::command().as_deref() {
+        Some("lint") => lint::run(&schema()),
+        Some("generate") => generate::run(generate_input, 323),
+        Some("search") => search(),
+        Some("draw") => draw(),
+        _ => {
+            let a = match slopes() {
+                Some(slopes) => Puzzle1::with_slopes(slopes),
+                None => Default::default(),
+            };
+            with_map_options(a).run();
+        }
+    }
 }
 
-#[derive(PartialEq, Copy, Clone, Debug)]
-enum GroundState {
-    Open,
-    Tree,
+fn read(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e))
 }
 
-type Line = Vec<GroundState>;
-
-#[derive(Default)]
-struct Puzzle1 {
-    slope: Vec<Line>,
-    max_line: usize
+// `--slopes "1,1 3,1"` or `--slopes-file slopes.txt` replace the two parts' slopes
+fn slopes() -> Option<Vec<Slope>> {
+    let text = match (args::value("--slopes"), args::value("--slopes-file")) {
+        (Some(slopes), _) => slopes,
+        (None, Some(path)) => read(&path),
+        (None, None) => return None,
+    };
+    Some(parse_slopes(&text).unwrap_or_else(|e| panic!("{}", e)))
 }
 
-impl Puzzle1 {
-    fn to_line(input: String) -> Line {
-        let mut line = Line::new();
-        for c in input.chars() {
-            let state = match c {
-                '#' => GroundState::Tree,
-                '.' => GroundState::Open,
-                _ => panic!()
-            };
-            line.push(state);
-        }
-
-        line
-    }
-
-    fn count_trees(&self, x_step: usize, y_step: usize) -> i64 {
-        let mut trees = 0;
-        let mut x = x_step;
-        let mut y = y_step;
-        while y < self.slope.len() {
-            if self.slope[y][x % self.max_line] == GroundState::Tree {
-                trees += 1;
-            }
-            x += x_step;
-            y += y_step;
-        }
-
-        trees
-    }
+// `--legend legend.txt` declares more terrain and its weights, `--pad` fills out ragged rows
+fn with_map_options(puzzle: Puzzle1) -> Puzzle1 {
+    let legend: Legend = args::value("--legend").map_or_else(Legend::default, |path| read(&path).parse().unwrap_or_else(|e| panic!("{}", e)));
+    let ragged = if args::flag("--pad") { Ragged::Pad } else { Ragged::Reject };
+    puzzle.legend(legend).ragged(ragged)
 }
 
-impl Puzzle for Puzzle1 {
-    type ParsedLine = String;
+fn load() -> Puzzle1 {
+    with_map_options(Puzzle1::default()).load(&common::read_input()).unwrap_or_else(|e| panic!("{}", e))
+}
 
-    fn process_item(&mut self, item: Self::ParsedLine) {
-        let line = Puzzle1::to_line(item);
-        self.max_line = max(self.max_line, line.len());
-        self.slope.push(line);
+// `search [--bounds 30,10]` tries every slope up to that far right and down
+fn search() {
+    let bounds: Slope = args::value("--bounds").map_or(Slope { right: 30, down: 10 }, |b| b.parse().unwrap_or_else(|e| panic!("{}", e)));
+    let map = load();
+    let (fewest, most) = if args::value("--legend").is_some() { ("Lowest cost", "Highest cost") } else { ("Fewest trees", "Most trees") };
+    match map.search(bounds.right, bounds.down) {
+        Some(search) => {
+            println!("Searched {} slopes up to {}", search.slopes, bounds);
+            println!("{}: {} going {}", fewest, search.fewest.1, search.fewest.0);
+            println!("{}: {} going {}", most, search.most.1, search.most.0);
+        },
+        None => println!("No slopes to search"),
     }
+}
 
-    fn final_result(&mut self) -> String {
-        let trees_a = self.count_trees(1, 1);
-        let trees_b = self.count_trees(3, 1);
-        let trees_c = self.count_trees(5, 1);
-        let trees_d = self.count_trees(7, 1);
-        let trees_e = self.count_trees(1, 2);
+// `draw [--slopes ...] [--plain] [--out path.png|path.ppm] [--scale 4]` shows the paths down the
+// map, part A's slope unless others are given
+fn draw() {
+    let map = load();
+    let paths = map.paths(&slopes().unwrap_or_else(|| vec![PART_A_SLOPE]));
+    print!("{}", paths.to_text(!args::flag("--plain")));
 
-        format!("{}, {}", trees_b, (trees_a * trees_b * trees_c * trees_d * trees_e))
+    if let Some(out) = args::value("--out") {
+        let scale = args::value("--scale").map_or(4, |s| s.parse().expect("Bad --scale"));
+        paths.write_image(Path::new(&out), scale).unwrap_or_else(|e| panic!("Failed to write {}: {}", out, e));
+        println!("Image written to {}", out);
     }
 }
@@ -0,0 +1,621 @@
+//! Day 4: Passport Processing. Counts the passports that have every required field, and then
+//! the ones whose fields are also valid.
+
+use common::{FilteredInputLine, ParallelPuzzle, Puzzle};
+use common::generate::Rng;
+use common::lint::Schema;
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Passports are blank line separated records of `key:value` fields. Each one comes back
+/// with its lines joined by spaces.
+pub fn parse(input: &str) -> Vec<String> {
+    let mut passports = Vec::new();
+    let mut building: Vec<&str> = Vec::new();
+    for line in input.lines().map(str::trim) {
+        if line.is_empty() {
+            if !building.is_empty() {
+                passports.push(building.join(" "));
+                building.clear();
+            }
+        } else {
+            building.push(line);
+        }
+    }
+    if !building.is_empty() {
+        passports.push(building.join(" "));
+    }
+    passports
+}
+
+/// Passports with every required field
+pub fn solve_a(input: &str) -> usize {
+    count_valid(input, RuleSet::Casual)
+}
+
+/// Passports with every required field, each holding a valid value
+pub fn solve_b(input: &str) -> usize {
+    count_valid(input, RuleSet::Strict)
+}
+
+fn count_valid(input: &str, rules: RuleSet) -> usize {
+    parse(input).iter().filter(|record| Passport::new(record).is_valid(rules)).count()
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new()
+        .records()
+        .token_pattern(r"(byr|iyr|eyr|hgt|hcl|ecl|pid|cid):\S+")
+        .unique_keys()
+}
+
+/// Mostly complete passports with a mix of valid and out of range values, spread over a few
+/// lines each
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let eye_colours = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth", "xry"];
+    let hex = "0123456789abcdef".chars().collect::<Vec<char>>();
+    let mut input = String::new();
+    for _ in 0..size {
+        let mut fields = vec![
+            format!("byr:{}", rng.range(1900, 2010)),
+            format!("iyr:{}", rng.range(2005, 2025)),
+            format!("eyr:{}", rng.range(2015, 2035)),
+            if rng.chance(0.5) {
+                format!("hgt:{}cm", rng.range(140, 200))
+            } else if rng.chance(0.8) {
+                format!("hgt:{}in", rng.range(55, 80))
+            } else {
+                format!("hgt:{}", rng.range(55, 200))
+            },
+            if rng.chance(0.9) {
+                format!("hcl:#{}", (0..6).map(|_| *rng.pick(&hex)).collect::<String>())
+            } else {
+                format!("hcl:{}", (0..6).map(|_| *rng.pick(&hex)).collect::<String>())
+            },
+            format!("ecl:{}", rng.pick(&eye_colours)),
+            if rng.chance(0.9) {
+                format!("pid:{:09}", rng.range(0, 999_999_999))
+            } else {
+                format!("pid:{:08}", rng.range(0, 99_999_999))
+            },
+        ];
+        if rng.chance(0.5) {
+            fields.push(format!("cid:{}", rng.range(100, 350)));
+        }
+        while rng.chance(0.2) && fields.len() > 1 {
+            let missing = rng.below(fields.len());
+            fields.remove(missing);
+        }
+        rng.shuffle(&mut fields);
+
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                input.push(if rng.chance(0.3) { '\n' } else { ' ' });
+            }
+            input.push_str(field);
+        }
+        input.push_str("\n\n");
+    }
+    input
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Hash, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum PassportFields {
+    Byr, Iyr, Eyr, Hgt, Hcl, Ecl, Pid,
+}
+
+impl fmt::Display for PassportFields {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let key = match self {
+            PassportFields::Byr => "byr",
+            PassportFields::Iyr => "iyr",
+            PassportFields::Eyr => "eyr",
+            PassportFields::Hgt => "hgt",
+            PassportFields::Hcl => "hcl",
+            PassportFields::Ecl => "ecl",
+            PassportFields::Pid => "pid",
+        };
+        write!(f, "{}", key)
+    }
+}
+
+fn digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// A four digit year, as used by `byr`, `iyr` and `eyr`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct Year(pub u32);
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct YearError(pub String);
+
+impl fmt::Display for YearError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected a four digit year, got '{}'", self.0)
+    }
+}
+
+impl Error for YearError {}
+
+impl FromStr for Year {
+    type Err = YearError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 4 && digits(s) {
+            Ok(Year(s.parse().unwrap()))
+        } else {
+            Err(YearError(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for Year {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Unit {
+    Cm,
+    In,
+}
+
+/// `hgt`, a number followed by `cm` or `in`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct Height {
+    pub value: u32,
+    pub unit: Unit,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum HeightError {
+    Number(String),
+    Unit(String),
+}
+
+impl fmt::Display for HeightError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeightError::Number(s) => write!(f, "'{}' doesn't start with a number", s),
+            HeightError::Unit(s) => write!(f, "unknown unit '{}', expected cm or in", s),
+        }
+    }
+}
+
+impl Error for HeightError {}
+
+impl FromStr for Height {
+    type Err = HeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let value = number.parse().map_err(|_| HeightError::Number(s.to_string()))?;
+        let unit = match unit {
+            "cm" => Unit::Cm,
+            "in" => Unit::In,
+            other => return Err(HeightError::Unit(other.to_string())),
+        };
+        Ok(Height { value, unit })
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.unit {
+            Unit::Cm => write!(f, "{}cm", self.value),
+            Unit::In => write!(f, "{}in", self.value),
+        }
+    }
+}
+
+/// `hcl`, written `#rrggbb` in lower case hex
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct HairColour {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum HairColourError {
+    NoHash(String),
+    Hex(String),
+}
+
+impl fmt::Display for HairColourError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HairColourError::NoHash(s) => write!(f, "'{}' doesn't start with #", s),
+            HairColourError::Hex(s) => write!(f, "'{}' isn't six lower case hex digits", s),
+        }
+    }
+}
+
+impl Error for HairColourError {}
+
+impl FromStr for HairColour {
+    type Err = HairColourError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').ok_or_else(|| HairColourError::NoHash(s.to_string()))?;
+        if hex.len() != 6 || !hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            return Err(HairColourError::Hex(hex.to_string()));
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+        Ok(HairColour { r: channel(0), g: channel(2), b: channel(4) })
+    }
+}
+
+impl fmt::Display for HairColour {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum EyeColour {
+    Amb, Blu, Brn, Gry, Grn, Hzl, Oth,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct EyeColourError(pub String);
+
+impl fmt::Display for EyeColourError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown eye colour '{}'", self.0)
+    }
+}
+
+impl Error for EyeColourError {}
+
+impl FromStr for EyeColour {
+    type Err = EyeColourError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "amb" => Ok(EyeColour::Amb),
+            "blu" => Ok(EyeColour::Blu),
+            "brn" => Ok(EyeColour::Brn),
+            "gry" => Ok(EyeColour::Gry),
+            "grn" => Ok(EyeColour::Grn),
+            "hzl" => Ok(EyeColour::Hzl),
+            "oth" => Ok(EyeColour::Oth),
+            _ => Err(EyeColourError(s.to_string())),
+        }
+    }
+}
+
+/// `pid`, nine digits including any leading zeros
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct PassportId(pub u32);
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PassportIdError(pub String);
+
+impl fmt::Display for PassportIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected nine digits, got '{}'", self.0)
+    }
+}
+
+impl Error for PassportIdError {}
+
+impl FromStr for PassportId {
+    type Err = PassportIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 9 && digits(s) {
+            Ok(PassportId(s.parse().unwrap()))
+        } else {
+            Err(PassportIdError(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for PassportId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:09}", self.0)
+    }
+}
+
+/// A field that's missing, or present and parsed, or present with its parser's error
+pub type Field<T, E> = Option<Result<T, E>>;
+
+/// One passport record, each field parsed to its type. `cid` is kept as it's written.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Passport {
+    pub birth_year: Field<Year, YearError>,
+    pub issue_year: Field<Year, YearError>,
+    pub expiration_year: Field<Year, YearError>,
+    pub height: Field<Height, HeightError>,
+    pub hair_colour: Field<HairColour, HairColourError>,
+    pub eye_colour: Field<EyeColour, EyeColourError>,
+    pub passport_id: Field<PassportId, PassportIdError>,
+    pub country_id: Option<String>,
+}
+
+/// Why a passport fails its rule set: the first field missing, or failing to parse, or out
+/// of range
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum PassportError {
+    Missing(PassportFields),
+    Invalid { field: PassportFields, reason: String },
+    OutOfRange { field: PassportFields, value: String },
+}
+
+impl fmt::Display for PassportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PassportError::Missing(field) => write!(f, "{} is missing", field),
+            PassportError::Invalid { field, reason } => write!(f, "{}: {}", field, reason),
+            PassportError::OutOfRange { field, value } => write!(f, "{}: {} is out of range", field, value),
+        }
+    }
+}
+
+impl Error for PassportError {}
+
+fn present<T, E>(field: PassportFields, value: &Field<T, E>) -> Result<&Result<T, E>, PassportError> {
+    value.as_ref().ok_or(PassportError::Missing(field))
+}
+
+fn parsed<T, E: fmt::Display>(field: PassportFields, value: &Result<T, E>) -> Result<&T, PassportError> {
+    value.as_ref().map_err(|e| PassportError::Invalid { field, reason: e.to_string() })
+}
+
+fn within(field: PassportFields, n: u32, range: RangeInclusive<u32>, value: impl fmt::Display) -> Result<(), PassportError> {
+    if range.contains(&n) {
+        Ok(())
+    } else {
+        Err(PassportError::OutOfRange { field, value: value.to_string() })
+    }
+}
+
+impl Passport {
+    /// Builds a passport from a record's whitespace separated `key:value` tokens. Tokens
+    /// with an unknown key are ignored, and a repeated key keeps its last value.
+    pub fn new(record: &str) -> Passport {
+        let mut passport = Passport::default();
+        for (key, value) in record.split_whitespace().filter_map(|t| t.split_once(':')) {
+            match key {
+                "byr" => passport.birth_year = Some(value.parse()),
+                "iyr" => passport.issue_year = Some(value.parse()),
+                "eyr" => passport.expiration_year = Some(value.parse()),
+                "hgt" => passport.height = Some(value.parse()),
+                "hcl" => passport.hair_colour = Some(value.parse()),
+                "ecl" => passport.eye_colour = Some(value.parse()),
+                "pid" => passport.passport_id = Some(value.parse()),
+                "cid" => passport.country_id = Some(value.to_string()),
+                _ => {},
+            }
+        }
+        passport
+    }
+
+    /// `Casual` only needs every field other than `cid`, `Strict` needs them all to parse and
+    /// be in range as well
+    pub fn check(&self, rules: RuleSet) -> Result<(), PassportError> {
+        use PassportFields::*;
+        let byr = present(Byr, &self.birth_year)?;
+        let iyr = present(Iyr, &self.issue_year)?;
+        let eyr = present(Eyr, &self.expiration_year)?;
+        let hgt = present(Hgt, &self.height)?;
+        let hcl = present(Hcl, &self.hair_colour)?;
+        let ecl = present(Ecl, &self.eye_colour)?;
+        let pid = present(Pid, &self.passport_id)?;
+        if rules == RuleSet::Casual {
+            return Ok(());
+        }
+
+        let year = parsed(Byr, byr)?;
+        within(Byr, year.0, 1920..=2002, year)?;
+        let year = parsed(Iyr, iyr)?;
+        within(Iyr, year.0, 2010..=2020, year)?;
+        let year = parsed(Eyr, eyr)?;
+        within(Eyr, year.0, 2020..=2030, year)?;
+        let height = parsed(Hgt, hgt)?;
+        let range = match height.unit {
+            Unit::Cm => 150..=193,
+            Unit::In => 59..=76,
+        };
+        within(Hgt, height.value, range, height)?;
+        parsed(Hcl, hcl)?;
+        parsed(Ecl, ecl)?;
+        parsed(Pid, pid)?;
+        Ok(())
+    }
+
+    pub fn is_valid(&self, rules: RuleSet) -> bool {
+        self.check(rules).is_ok()
+    }
+}
+
+/// `Casual` only needs the fields to be present, `Strict` checks their values as well
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub enum RuleSet {
+    Strict,
+    #[default]
+    Casual,
+}
+
+/// Either part as a `ParallelPuzzle`, depending on the rule set
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+pub struct Puzzle1 {
+    rules: RuleSet,
+    building: Vec<String>,
+    valid_count: usize,
+}
+
+impl Puzzle1 {
+    pub fn new(rules: RuleSet) -> Puzzle1 {
+        Puzzle1 { rules, ..Default::default() }
+    }
+
+    fn finalise_passport(&mut self) {
+        if self.building.is_empty() {
+            return;
+        }
+
+        let passport = Passport::new(&self.building.join(" "));
+
+        // Get ready for the next build
+        self.building.clear();
+
+        if passport.is_valid(self.rules) {
+            self.valid_count += 1;
+        }
+    }
+}
+
+impl Puzzle for Puzzle1 {
+    type ParsedLine = String;
+
+    // Blank lines separate passports, so they have to reach process_item in order
+    fn filter_line(&mut self, _line: &str) -> FilteredInputLine {
+        FilteredInputLine::Process
+    }
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        let tokens = self.map_item(item);
+        self.reduce_item(tokens);
+    }
+
+    fn final_result(&mut self) -> String {
+        self.finalise_passport();
+        self.valid_count.to_string()
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+impl ParallelPuzzle for Puzzle1 {
+    // The line's `key:value` tokens, with None marking the end of a passport
+    type Mapped = Option<Vec<String>>;
+
+    fn map_item(&self, item: Self::ParsedLine) -> Self::Mapped {
+        if item.trim().is_empty() {
+            None
+        } else {
+            Some(item.split_whitespace().map(String::from).collect())
+        }
+    }
+
+    fn reduce_item(&mut self, tokens: Self::Mapped) {
+        match tokens {
+            None => self.finalise_passport(),
+            Some(t) => self.building.extend(t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+
+    #[test]
+    fn example_1() {
+        let input = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm\n\niyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884\nhcl:#cfa07d byr:1929\n\nhcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm\n\nhcl:#cfa07d eyr:2025 pid:166559648\niyr:2011 ecl:brn hgt:59in\n".to_string();
+        let mut subject: Puzzle1 = Puzzle1::new(RuleSet::Casual);
+        subject.run_with_input(input);
+        assert_eq!(2, subject.valid_count);
+    }
+
+    #[test]
+    fn example_1_parallel() {
+        let input = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm\n\niyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884\nhcl:#cfa07d byr:1929\n\nhcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm\n\nhcl:#cfa07d eyr:2025 pid:166559648\niyr:2011 ecl:brn hgt:59in\n".to_string();
+        let mut subject: Puzzle1 = Puzzle1::new(RuleSet::Casual);
+        subject.run_parallel_with_input(input);
+        assert_eq!(2, subject.valid_count);
+    }
+
+    #[test]
+    fn solve() {
+        let input = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm\n\niyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884\nhcl:#cfa07d byr:1929\n\nhcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm\n\nhcl:#cfa07d eyr:2025 pid:166559648\niyr:2011 ecl:brn hgt:59in\n";
+        assert_eq!(4, parse(input).len());
+        assert_eq!(2, solve_a(input));
+        assert_eq!(2, solve_b(input));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 300);
+        assert!(schema().lint(&input).is_empty());
+
+        let mut casual: Puzzle1 = Puzzle1::new(RuleSet::Casual);
+        casual.run_with_input(input.clone());
+        let mut strict: Puzzle1 = Puzzle1::new(RuleSet::Strict);
+        strict.run_parallel_with_input(input);
+        assert!(casual.valid_count > strict.valid_count);
+        assert!(strict.valid_count > 0);
+    }
+
+    #[test]
+    fn field_parsers() {
+        assert_eq!(Ok(Year(2002)), "2002".parse());
+        assert_eq!(Err(YearError("20022".to_string())), "20022".parse::<Year>());
+        assert_eq!(Ok(Height { value: 60, unit: Unit::In }), "60in".parse());
+        assert_eq!("190cm", "190cm".parse::<Height>().unwrap().to_string());
+        assert_eq!(Err(HeightError::Unit("".to_string())), "190".parse::<Height>());
+        assert_eq!(Err(HeightError::Number("cm".to_string())), "cm".parse::<Height>());
+        assert_eq!(Ok(HairColour { r: 0x12, g: 0x3a, b: 0xbc }), "#123abc".parse());
+        assert_eq!(Err(HairColourError::Hex("123abz".to_string())), "#123abz".parse::<HairColour>());
+        assert_eq!(Err(HairColourError::NoHash("123abc".to_string())), "123abc".parse::<HairColour>());
+        assert_eq!(Ok(EyeColour::Brn), "brn".parse());
+        assert_eq!(Err(EyeColourError("wat".to_string())), "wat".parse::<EyeColour>());
+        assert_eq!("000000001", "000000001".parse::<PassportId>().unwrap().to_string());
+        assert!("0123456789".parse::<PassportId>().is_err());
+    }
+
+    #[test]
+    fn passport_checks() {
+        let passport = Passport::new("eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926");
+        assert_eq!(Some("100".to_string()), passport.country_id);
+        assert_eq!(Ok(()), passport.check(RuleSet::Casual));
+        assert_eq!(Err(PassportError::OutOfRange { field: PassportFields::Eyr, value: "1972".to_string() }), passport.check(RuleSet::Strict));
+
+        let passport = Passport::new("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f");
+        assert_eq!(Some(Ok(HairColour { r: 0x62, g: 0x3a, b: 0x2f })), passport.hair_colour);
+        assert_eq!(Ok(()), passport.check(RuleSet::Strict));
+
+        let passport = Passport::new("hgt:59cm ecl:zzz eyr:2038 hcl:74454a iyr:2023 pid:3556412378 byr:2007");
+        assert_eq!("byr: 2007 is out of range", passport.check(RuleSet::Strict).unwrap_err().to_string());
+        let passport = Passport::new("hcl:#cfa07d byr:1929 iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884");
+        assert_eq!(Err(PassportError::Missing(PassportFields::Hgt)), passport.check(RuleSet::Casual));
+        let passport = Passport::new("hcl:dab227 iyr:2012 ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277");
+        assert_eq!("hcl: 'dab227' doesn't start with #", passport.check(RuleSet::Strict).unwrap_err().to_string());
+    }
+
+    #[test]
+    fn strict_examples() {
+        let invalid = "eyr:1972 cid:100\nhcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926\n\niyr:2019\nhcl:#602927 eyr:1967 hgt:170cm\necl:grn pid:012533040 byr:1946\n\nhcl:dab227 iyr:2012\necl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277\n\nhgt:59cm ecl:zzz\neyr:2038 hcl:74454a iyr:2023\npid:3556412378 byr:2007\n";
+        let valid = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\nhcl:#623a2f\n\neyr:2029 ecl:blu cid:129 byr:1989\niyr:2014 pid:896056539 hcl:#a97842 hgt:165cm\n\nhcl:#888785\nhgt:164cm byr:2001 iyr:2015 cid:88\npid:545766238 ecl:hzl\neyr:2022\n\niyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719\n";
+        assert_eq!((4, 0), (solve_a(invalid), solve_b(invalid)));
+        assert_eq!((4, 4), (solve_a(valid), solve_b(valid)));
+    }
+}
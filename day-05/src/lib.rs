@@ -0,0 +1,266 @@
+//! Day 5: Binary Boarding. Decodes binary space partitioned boarding passes into seat ids.
+
+use common::{ParallelPuzzle, Puzzle};
+use common::generate::Rng;
+use common::lint::Schema;
+use std::collections::HashSet;
+
+/// Seat id of every boarding pass, in input order
+pub fn parse(input: &str) -> Vec<i32> {
+    input.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| Puzzle1::id(l.trim()))
+        .collect()
+}
+
+/// Highest seat id
+pub fn solve_a(input: &str) -> Option<i32> {
+    parse(input).into_iter().max()
+}
+
+/// The one missing seat id with taken seats either side of it
+pub fn solve_b(input: &str) -> Option<i32> {
+    let mut ids = parse(input);
+    ids.sort_unstable();
+    find_gap(&ids)
+}
+
+// First id missing from a sorted run of ids
+fn find_gap(sorted_ids: &[i32]) -> Option<i32> {
+    sorted_ids.windows(2)
+        .find(|pair| pair[0] + 1 != pair[1])
+        .map(|pair| pair[0] + 1)
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().allowed_chars("FBLR").line_pattern("[FB]{7}[LR]{3}")
+}
+
+/// A contiguous block of seats with one missing from the middle. The plane only has so many
+/// seats, so size is capped at 1000.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let block = size.clamp(2, 1000) as i64 + 1;
+    let first = rng.range(8, 1016 - block);
+    let mine = rng.range(first + 1, first + block - 2);
+
+    let mut ids: Vec<i64> = (first..first + block).filter(|id| *id != mine).collect();
+    rng.shuffle(&mut ids);
+
+    ids.iter().map(|id| format!("{}\n", Puzzle1::pass(*id as i32))).collect()
+}
+
+/// Both parts as a `ParallelPuzzle`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+pub struct Puzzle1 {
+    seating_ids: Vec<i32>,
+}
+
+impl Puzzle1 {
+    /// Seat id of a pass such as `FBFBBFFRLR`
+    pub fn id(pass: &str) -> i32 {
+        let mut row = SearchRange { low: 0, high: 127 };
+        let mut col = SearchRange { low: 0, high: 7 };
+        for c in pass.chars() {
+            match c {
+                'F' => row.half_low(),
+                'B' => row.half_high(),
+                'R' => col.half_high(),
+                'L' => col.half_low(),
+                _ => panic!(),
+            }
+        }
+        row.result().unwrap() * 8 + col.result().unwrap()
+    }
+
+    /// Pass for a seat id. The id is just the pass read as a 10 bit number, high bits first.
+    pub fn pass(id: i32) -> String {
+        let mut pass = String::new();
+        for bit in (3..10).rev() {
+            pass.push(if id & (1 << bit) == 0 { 'F' } else { 'B' });
+        }
+        for bit in (0..3).rev() {
+            pass.push(if id & (1 << bit) == 0 { 'L' } else { 'R' });
+        }
+        pass
+    }
+
+    #[allow(dead_code)]
+    fn possible_ids() -> HashSet<i32> {
+        let mut ids = HashSet::new();
+        for r in 1..127i32 {
+            for c in 1..7i32 {
+                let id = r * 8 + c;
+                ids.insert(id);
+            }
+        }
+        ids
+    }
+}
+
+struct SearchRange {
+    low: i32,
+    high: i32,
+}
+
+impl SearchRange {
+    fn mid(&self) -> i32 {
+        ((self.high - self.low) / 2) + self.low
+    }
+
+    fn half_high(&mut self) {
+        self.low = self.mid() + 1;
+    }
+
+    fn half_low(&mut self) {
+        self.high = self.mid();
+    }
+
+    fn result(&self) -> Option<i32> {
+        if self.low != self.high {
+            None
+        } else {
+            Some(self.low)
+        }
+    }
+}
+
+impl Puzzle for Puzzle1 {
+    type ParsedLine = String;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        let id = self.map_item(item);
+        self.reduce_item(id);
+    }
+
+    fn final_result(&mut self) -> String {
+        self.seating_ids.sort();
+        let my_seat = find_gap(&self.seating_ids);
+
+        let max_id = self.seating_ids.iter().max().unwrap();
+
+        format!("max: {}, my seat: {}", max_id, my_seat.unwrap())
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+impl ParallelPuzzle for Puzzle1 {
+    type Mapped = i32;
+
+    fn map_item(&self, item: Self::ParsedLine) -> Self::Mapped {
+        Puzzle1::id(&item)
+    }
+
+    fn reduce_item(&mut self, id: Self::Mapped) {
+        self.seating_ids.push(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+
+    #[test]
+    fn example_1_row() {
+        let mut row = SearchRange { low: 0, high: 127 };
+
+        row.half_low();
+        assert_eq!(0, row.low);
+        assert_eq!(63, row.high);
+
+        row.half_high();
+        assert_eq!(32, row.low);
+        assert_eq!(63, row.high);
+
+        row.half_low();
+        assert_eq!(32, row.low);
+        assert_eq!(47, row.high);
+
+        row.half_high();
+        assert_eq!(40, row.low);
+        assert_eq!(47, row.high);
+
+        row.half_high();
+        assert_eq!(44, row.low);
+        assert_eq!(47, row.high);
+
+        row.half_low();
+        assert_eq!(44, row.low);
+        assert_eq!(45, row.high);
+
+        row.half_low();
+        assert_eq!(44, row.low);
+        assert_eq!(44, row.high);
+
+        assert_eq!(44, row.result().unwrap());
+    }
+
+    #[test]
+    fn example_1_col() {
+        let mut col = SearchRange { low: 0, high: 7 };
+
+        col.half_high();
+        assert_eq!(4, col.low);
+        assert_eq!(7, col.high);
+
+        col.half_low();
+        assert_eq!(4, col.low);
+        assert_eq!(5, col.high);
+
+        col.half_high();
+        assert_eq!(5, col.low);
+        assert_eq!(5, col.high);
+
+        assert_eq!(5, col.result().unwrap());
+    }
+
+    #[test]
+    fn example_1_id() {
+        let input_a = "FBFBBFFRLR".to_string();
+        assert_eq!(357, Puzzle1::id(&input_a));
+        let input_a = "BFFFBBFRRR".to_string();
+        assert_eq!(567, Puzzle1::id(&input_a));
+        let input_a = "FFFBBBFRRR".to_string();
+        assert_eq!(119, Puzzle1::id(&input_a));
+        let input_a = "BBFFBBFRLL".to_string();
+        assert_eq!(820, Puzzle1::id(&input_a));
+    }
+
+    #[test]
+    fn solve() {
+        let input = "FBFBBFFRLR\nFBFBBFFRRR\nFBFBBFBLLR\n";
+        assert_eq!(vec![357, 359, 361], parse(input));
+        assert_eq!(Some(361), solve_a(input));
+        assert_eq!(Some(358), solve_b(input));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 500);
+        assert!(schema().lint(&input).is_empty());
+
+        let mut subject: Puzzle1 = Default::default();
+        let report = subject.run_parallel_with_input(input);
+        assert_eq!(500, subject.seating_ids.len());
+        assert!(report.result.starts_with("max: "));
+    }
+
+    #[test]
+    fn pass_round_trip() {
+        generate::assert_round_trip(500, |rng| rng.range(0, 1023) as i32, |id| Puzzle1::pass(*id), Puzzle1::id);
+
+        let letters: Vec<(char, char)> = vec![('F', 'B'); 7].into_iter().chain(vec![('L', 'R'); 3]).collect();
+        generate::assert_round_trip(
+            500,
+            |rng| letters.iter().map(|(low, high)| if rng.chance(0.5) { *low } else { *high }).collect::<String>(),
+            |pass| Puzzle1::id(pass).to_string(),
+            |id| Puzzle1::pass(id.parse().unwrap()),
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Day 6: Custom Customs. Tallies the yes answers of each group of passengers.
+
+use common::Puzzle;
+use common::generate::Rng;
+use common::lint::Schema;
+use common::FilteredInputLine;
+use std::collections::{HashSet, HashMap};
+
+/// Groups are separated by blank lines, with one line of yes answers per person
+pub fn parse(input: &str) -> Vec<Vec<String>> {
+    let mut groups = Vec::new();
+    let mut group = Vec::new();
+    for line in input.lines().map(str::trim) {
+        if line.is_empty() {
+            if !group.is_empty() {
+                groups.push(std::mem::take(&mut group));
+            }
+        } else {
+            group.push(line.to_string());
+        }
+    }
+    if !group.is_empty() {
+        groups.push(group);
+    }
+    groups
+}
+
+/// Sum over the groups of questions anyone answered yes to
+pub fn solve_a(input: &str) -> usize {
+    Puzzle1::from_groups(&parse(input)).sum
+}
+
+/// Sum over the groups of questions everyone answered yes to
+pub fn solve_b(input: &str) -> usize {
+    Puzzle1::from_groups(&parse(input)).better_sum
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().records().allowed_chars("abcdefghijklmnopqrstuvwxyz")
+}
+
+/// A valid input of roughly `size` lines for the `generate` command
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let questions: Vec<char> = "abcdefghijklmnopqrstuvwxyz".chars().collect();
+    let mut input = String::new();
+    for _ in 0..size {
+        let people = rng.range(1, 5);
+        for _ in 0..people {
+            let mut answers = questions.clone();
+            rng.shuffle(&mut answers);
+            let count = rng.range(1, 26) as usize;
+            input.extend(&answers[..count]);
+            input.push('\n');
+        }
+        input.push('\n');
+    }
+    input
+}
+
+/// Both parts as a `Puzzle`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+pub struct Puzzle1 {
+    building: HashSet<char>,
+    building_count: HashMap<char, usize>,
+    num_people: usize,
+
+    sum: usize,
+    better_sum: usize,
+}
+
+impl Puzzle1 {
+    fn from_groups(groups: &[Vec<String>]) -> Puzzle1 {
+        let mut puzzle: Puzzle1 = Default::default();
+        for group in groups {
+            for person in group {
+                puzzle.process_item(person.clone());
+            }
+            puzzle.finalise();
+        }
+        puzzle
+    }
+
+    fn finalise(&mut self) {
+        self.sum += self.building.len();
+        self.building.clear();
+
+        for hits in self.building_count.values() {
+            if *hits == self.num_people {
+                self.better_sum += 1;
+            }
+        }
+        self.building_count.clear();
+        self.num_people = 0;
+    }
+}
+
+impl Puzzle for Puzzle1 {
+    type ParsedLine = String;
+
+    fn filter_line(&mut self, line: &str) -> FilteredInputLine {
+        let filtered = self.default_filter_line(line);
+        if let FilteredInputLine::Skip = filtered  {
+            self.finalise();
+        }
+        filtered
+    }
+
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        for answer in item.chars() {
+            self.building.insert(answer);
+            *self.building_count.entry(answer).or_insert(0) += 1;
+        }
+        self.num_people += 1;
+    }
+
+    fn final_result(&mut self) -> String {
+        self.finalise();
+
+        format!("sum: {}, better_sum: {}", self.sum, self.better_sum)
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+
+    #[test]
+    fn example_1() {
+        let input = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb".to_string();
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+        assert_eq!(11, subject.sum);
+        assert_eq!(6, subject.better_sum);
+    }
+
+    #[test]
+    fn solve() {
+        let input = "abc\n\na\nb\nc\n\nab\nac\n\n\na\na\na\na\n\nb";
+        assert_eq!(5, parse(input).len());
+        assert_eq!(11, solve_a(input));
+        assert_eq!(6, solve_b(input));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 500);
+        assert!(schema().lint(&input).is_empty());
+
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+        assert!(subject.sum >= subject.better_sum);
+        assert!(subject.sum >= 500);
+    }
+}
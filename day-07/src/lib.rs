@@ -0,0 +1,400 @@
+//! Day 7: Handy Haversacks. Follows the luggage rules to find which bags can hold a shiny gold
+//! bag, and how many bags it has to hold.
+
+use common::Puzzle;
+use common::generate::Rng;
+use common::lint::Schema;
+use common::variants::Variant;
+use std::collections::{HashMap, HashSet};
+use regex::Regex;
+
+/// The rules as a graph from each bag colour to the bags it directly contains
+pub fn parse(input: &str) -> HashMap<String, Vec<Rule>> {
+    let mut puzzle = Puzzle1::new();
+    for line in input.lines().filter(|l| !l.trim().is_empty()) {
+        puzzle.process_item(line.to_string());
+    }
+    puzzle.bag_rules
+}
+
+/// Bag colours that can eventually contain a shiny gold bag
+pub fn solve_a(input: &str) -> usize {
+    Puzzle1::from_rules(parse(input)).count_containers_of("shiny gold")
+}
+
+/// Bags inside a single shiny gold bag
+pub fn solve_b(input: &str) -> usize {
+    Puzzle1::from_rules(parse(input)).count_contained_by("shiny gold")
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().line_pattern(r"\w+ \w+ bags contain (no other bags|\d+ \w+ \w+ bags?(, \d+ \w+ \w+ bags?)*)\.")
+}
+
+/// Bags are put in a random order and may only contain bags later in that order, so the rules
+/// always form a DAG. Shiny gold sits near the end to keep its contents count from exploding.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let adjectives = [
+        "light", "dark", "bright", "muted", "shiny", "faded", "dotted", "vibrant", "pale", "wavy",
+        "plaid", "striped", "posh", "dull", "drab", "clear", "dim", "mirrored", "dusty", "faint",
+    ];
+    let colours = [
+        "red", "orange", "white", "yellow", "gold", "olive", "plum", "blue", "black", "green",
+        "teal", "tan", "violet", "cyan", "magenta", "lime", "coral", "beige", "maroon", "silver",
+    ];
+
+    let mut names: Vec<String> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    seen.insert(String::from("shiny gold"));
+    let mut suffix = 0;
+    while names.len() + 1 < size.max(2) {
+        let adjective = rng.pick(&adjectives);
+        let colour = rng.pick(&colours);
+        let name = if seen.len() < adjectives.len() * colours.len() / 2 {
+            format!("{} {}", adjective, colour)
+        } else {
+            // Digits would confuse the rule parser, so extra names get a letter suffix
+            suffix += 1;
+            let mut tag = String::new();
+            let mut n = suffix;
+            while n > 0 {
+                tag.push((b'a' + (n % 26) as u8) as char);
+                n /= 26;
+            }
+            format!("{}{} {}", adjective, tag, colour)
+        };
+        if seen.insert(name.clone()) {
+            names.push(name);
+        }
+    }
+    rng.shuffle(&mut names);
+    let gold_at = names.len() - (names.len() / 2).min(10);
+    names.insert(gold_at, String::from("shiny gold"));
+
+    let mut lines = Vec::new();
+    for (i, name) in names.iter().enumerate() {
+        let later = names.len() - i - 1;
+        let mut contents: Vec<Rule> = Vec::new();
+        let mut used = HashSet::new();
+        if i + 1 == gold_at {
+            // Make sure something holds the shiny gold bag
+            used.insert(gold_at);
+            contents.push(Rule { colour: String::from("shiny gold"), quantity: 1 });
+        }
+        for _ in 0..rng.range(0, 3.min(later as i64)) {
+            let inner = i + 1 + rng.below(later);
+            if used.insert(inner) {
+                let quantity = rng.range(1, 5) as usize;
+                contents.push(Rule { colour: names[inner].clone(), quantity });
+            }
+        }
+        lines.push(line_from_rules(name, &contents) + "\n");
+    }
+    rng.shuffle(&mut lines);
+
+    lines.concat()
+}
+
+/// `quantity` bags of `colour` inside another bag
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(PartialEq, Debug)]
+pub struct Rule {
+    pub colour: String,
+    pub quantity: usize,
+}
+
+/// Canonical line for a bag and its contents, the inverse of parsing
+pub fn line_from_rules(bag_colour: &str, rules: &[Rule]) -> String {
+    if rules.is_empty() {
+        return format!("{} bags contain no other bags.", bag_colour);
+    }
+
+    let contents: Vec<String> = rules.iter()
+        .map(|r| format!("{} {} bag{}", r.quantity, r.colour, if r.quantity == 1 { "" } else { "s" }))
+        .collect();
+    format!("{} bags contain {}.", bag_colour, contents.join(", "))
+}
+
+// Indexed builds a contained-in index once and memoises contents counts, rather than
+// rescanning every rule for each bag
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Copy)]
+enum Strategy {
+    Rescan,
+    Indexed,
+}
+
+/// Both parts as a `Puzzle`, with a variant per strategy
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Puzzle1 {
+    bag_rules: HashMap<String, Vec<Rule>>,
+    strategy: Strategy,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bag_colour_matcher: Regex,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bag_rule_matcher: Regex,
+}
+
+impl Puzzle1 {
+    pub fn new() -> Puzzle1 {
+        Puzzle1::with_strategy(Strategy::Rescan)
+    }
+
+    pub fn from_rules(bag_rules: HashMap<String, Vec<Rule>>) -> Puzzle1 {
+        Puzzle1 { bag_rules, ..Puzzle1::with_strategy(Strategy::Indexed) }
+    }
+
+    fn with_strategy(strategy: Strategy) -> Puzzle1 {
+        Puzzle1 {
+            bag_colour_matcher: Regex::new(r"^(.+?) bag").unwrap(),
+            bag_rule_matcher: Regex::new(r"(\d+?) (.+?) bag").unwrap(),
+
+            bag_rules: Default::default(),
+            strategy,
+        }
+    }
+
+    pub fn variants() -> Vec<Variant<Puzzle1>> {
+        vec![
+            Variant { name: "rescan", puzzle: || Puzzle1::with_strategy(Strategy::Rescan) },
+            Variant { name: "indexed", puzzle: || Puzzle1::with_strategy(Strategy::Indexed) },
+        ]
+    }
+
+    fn rules_from_line(&self, line: String) -> (String, Vec<Rule>) {
+        let bag_colour = self.bag_colour_matcher
+            .captures(&line)
+            .unwrap()
+            .get(1)
+            .unwrap()
+            .as_str().to_string();
+        let mut rules = Vec::new();
+        for capture in self.bag_rule_matcher.captures_iter(&line) {
+            let quantity = capture.get(1).unwrap().as_str().parse::<usize>().unwrap();
+            let colour = capture.get(2).unwrap().as_str().to_string();
+
+            rules.push( Rule { quantity, colour, } );
+        }
+
+        (bag_colour, rules)
+    }
+
+    pub fn count_containers_of(&self, bag: &str) -> usize {
+        match self.strategy {
+            Strategy::Rescan => {
+                let mut hits: HashSet<String> = HashSet::new();
+                self.find_all_containers(bag, &mut hits);
+                hits.len()
+            },
+            Strategy::Indexed => self.count_containers_indexed(bag),
+        }
+    }
+
+    fn count_containers_indexed(&self, bag: &str) -> usize {
+        let mut contained_in: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (colour, rules) in &self.bag_rules {
+            for rule in rules {
+                contained_in.entry(&rule.colour).or_default().push(colour);
+            }
+        }
+
+        let mut hits: HashSet<&str> = HashSet::new();
+        let mut pending = vec![bag];
+        while let Some(search_colour) = pending.pop() {
+            for colour in contained_in.get(search_colour).into_iter().flatten() {
+                if hits.insert(colour) {
+                    pending.push(colour);
+                }
+            }
+        }
+        hits.len()
+    }
+
+    fn find_all_containers(&self, search_colour: &str, hits: &mut HashSet<String>) {
+        for (colour, rules) in &self.bag_rules {
+            for rule in rules {
+                if rule.colour == search_colour && !hits.contains(colour){
+                    hits.insert(colour.clone());
+                    self.find_all_containers(colour, hits);
+                }
+            }
+        }
+    }
+
+    pub fn count_contained_by(&self, bag: &str) -> usize {
+        match self.strategy {
+            Strategy::Rescan => self.count_contained_by_recursive(bag),
+            Strategy::Indexed => self.count_contained_by_memoised(bag, &mut HashMap::new()),
+        }
+    }
+
+    fn count_contained_by_memoised<'a>(&'a self, bag: &'a str, memo: &mut HashMap<&'a str, usize>) -> usize {
+        if let Some(count) = memo.get(bag) {
+            return *count;
+        }
+        let mut count = 0usize;
+        for rule in &self.bag_rules[bag] {
+            count += rule.quantity;
+            count += self.count_contained_by_memoised(&rule.colour, memo) * rule.quantity;
+        }
+        memo.insert(bag, count);
+        count
+    }
+
+    fn count_contained_by_recursive(&self, bag: &str) -> usize {
+        let mut count = 0usize;
+        for rule in &self.bag_rules[bag] {
+            count += &rule.quantity;
+            count += self.count_contained_by_recursive(&rule.colour) * rule.quantity;
+        }
+        count
+    }
+}
+
+impl Default for Puzzle1 {
+    fn default() -> Puzzle1 {
+        Puzzle1::new()
+    }
+}
+
+impl Puzzle for Puzzle1 {
+    type ParsedLine = String;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        let (bag, rules) = self.rules_from_line(item);
+        let existing_rules = self.bag_rules.entry(bag).or_default();
+        for rule in rules {
+            existing_rules.push(rule);
+        }
+    }
+
+    fn final_result(&mut self) -> String {
+        let containers = self.count_containers_of("shiny gold");
+        let contained = self.count_contained_by("shiny gold");
+
+        format!("Containers of: {}; contained by: {}", containers, contained)
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+    use common::variants;
+
+    #[test]
+    fn example_line_parser() {
+        let input = "dark orange bags contain 3 bright white bags, 4 muted yellow bags.".to_string();
+        let subject = Puzzle1::new();
+        let (bag_colour, rules) = subject.rules_from_line(input);
+
+        assert_eq!("dark orange", bag_colour);
+        assert_eq!("bright white", rules[0].colour);
+        assert_eq!(3, rules[0].quantity);
+        assert_eq!("muted yellow", rules[1].colour);
+        assert_eq!(4, rules[1].quantity);
+    }
+
+    #[test]
+    fn example_line_parser_2() {
+        let input = "faded blue bags contain no other bags.".to_string();
+        let subject = Puzzle1::new();
+        let (bag_colour, rules) = subject.rules_from_line(input);
+
+        assert_eq!("faded blue", bag_colour);
+        assert_eq!(0, rules.len());
+    }
+
+    #[test]
+    fn example_1() {
+        let input = "light red bags contain 1 bright white bag, 2 muted yellow bags.\ndark orange bags contain 3 bright white bags, 4 muted yellow bags.\nbright white bags contain 1 shiny gold bag.\nmuted yellow bags contain 2 shiny gold bags, 9 faded blue bags.\nshiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.\ndark olive bags contain 3 faded blue bags, 4 dotted black bags.\nvibrant plum bags contain 5 faded blue bags, 6 dotted black bags.\nfaded blue bags contain no other bags.\ndotted black bags contain no other bags.".to_string();
+        let mut subject = Puzzle1::new();
+        subject.run_with_input(input);
+
+        assert_eq!(9, subject.bag_rules.len());
+        assert_eq!(4, subject.count_containers_of("shiny gold"));
+    }
+
+    #[test]
+    fn example_2() {
+        let input = "shiny gold bags contain 2 dark red bags.\ndark red bags contain 2 dark orange bags.\ndark orange bags contain 2 dark yellow bags.\ndark yellow bags contain 2 dark green bags.\ndark green bags contain 2 dark blue bags.\ndark blue bags contain 2 dark violet bags.\ndark violet bags contain no other bags".to_string();
+        let mut subject = Puzzle1::new();
+        subject.run_with_input(input);
+
+        assert_eq!(7, subject.bag_rules.len());
+        assert_eq!(126, subject.count_contained_by("shiny gold"));
+    }
+
+    #[test]
+    fn solve() {
+        let input = "light red bags contain 1 bright white bag, 2 muted yellow bags.\ndark orange bags contain 3 bright white bags, 4 muted yellow bags.\nbright white bags contain 1 shiny gold bag.\nmuted yellow bags contain 2 shiny gold bags, 9 faded blue bags.\nshiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.\ndark olive bags contain 3 faded blue bags, 4 dotted black bags.\nvibrant plum bags contain 5 faded blue bags, 6 dotted black bags.\nfaded blue bags contain no other bags.\ndotted black bags contain no other bags.";
+        assert_eq!(9, parse(input).len());
+        assert_eq!(4, solve_a(input));
+        assert_eq!(32, solve_b(input));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 600);
+        assert!(schema().lint(&input).is_empty());
+
+        let mut subject = Puzzle1::new();
+        subject.run_with_input(input);
+        assert_eq!(600, subject.bag_rules.len());
+        assert!(subject.count_containers_of("shiny gold") > 0);
+    }
+
+    #[test]
+    fn variants_agree() {
+        for seed in 0..5 {
+            let input = generate::generate(generate_input, seed, 400);
+            variants::assert_agree(&Puzzle1::variants(), &input);
+        }
+
+        let example = "light red bags contain 1 bright white bag, 2 muted yellow bags.\ndark orange bags contain 3 bright white bags, 4 muted yellow bags.\nbright white bags contain 1 shiny gold bag.\nmuted yellow bags contain 2 shiny gold bags, 9 faded blue bags.\nshiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.\ndark olive bags contain 3 faded blue bags, 4 dotted black bags.\nvibrant plum bags contain 5 faded blue bags, 6 dotted black bags.\nfaded blue bags contain no other bags.\ndotted black bags contain no other bags.\n";
+        variants::assert_agree(&Puzzle1::variants(), example);
+
+        if let Some(input) = variants::real_input() {
+            variants::assert_agree(&Puzzle1::variants(), &input);
+        }
+    }
+
+    // Colours are one to three words, none starting with "bag"
+    fn arbitrary_colour(rng: &mut Rng) -> String {
+        let letters: Vec<char> = "abcdefghijklmnopqrstuvwxyz".chars().collect();
+        let mut words = Vec::new();
+        while words.len() < rng.range(1, 3) as usize {
+            let word: String = (0..rng.range(1, 8)).map(|_| *rng.pick(&letters)).collect();
+            if !word.starts_with("bag") {
+                words.push(word);
+            }
+        }
+        words.join(" ")
+    }
+
+    #[test]
+    fn rules_round_trip() {
+        let subject = Puzzle1::new();
+        generate::assert_round_trip(
+            300,
+            |rng| {
+                let rules = (0..rng.range(0, 4))
+                    .map(|_| {
+                        let quantity = if rng.chance(0.1) { rng.range(1, u32::MAX as i64) } else { rng.range(1, 9) };
+                        Rule { colour: arbitrary_colour(rng), quantity: quantity as usize }
+                    })
+                    .collect::<Vec<Rule>>();
+                (arbitrary_colour(rng), rules)
+            },
+            |(bag_colour, rules)| line_from_rules(bag_colour, rules),
+            |line| subject.rules_from_line(line.to_string()),
+        );
+    }
+}
@@ -0,0 +1,531 @@
+//! Day 8: Handheld Halting. A tiny accumulator machine, run until it loops, then patched so
+//! it runs to the end.
+
+use common::Puzzle;
+use common::generate::Rng;
+use common::lint::Schema;
+use common::observer;
+use regex::Regex;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::mem::discriminant;
+
+/// The boot code, one instruction per line
+pub fn parse(input: &str) -> Vec<Instruction> {
+    input.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.trim().parse().unwrap())
+        .collect()
+}
+
+/// Accumulator just before any instruction runs a second time
+pub fn solve_a(input: &str) -> i64 {
+    let mut puzzle = Puzzle1::from_program(parse(input));
+    puzzle.run_till_loop();
+    puzzle.cpu.acc
+}
+
+/// Accumulator once the program runs to the end, after swapping a single jmp or nop. None if
+/// no single swap fixes it.
+pub fn solve_b(input: &str) -> Option<i64> {
+    let mut puzzle = Puzzle1::from_program(parse(input));
+    match puzzle.try_to_correct() {
+        TerminationReason::Complete => Some(puzzle.cpu.acc),
+        TerminationReason::Loop => None,
+    }
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().line_pattern(r"(acc|jmp|nop) [+-]\d+")
+}
+
+/// Builds a program that runs off the end, then turns one executed nop into a jmp back to an
+/// earlier executed instruction. The original loops, and patching that jmp back fixes it.
+/// Programs where some other single swap also runs to the end are thrown away.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    loop {
+        let program = plant(rng, size.max(3));
+        if fixes(&program).len() == 1 {
+            return program.iter().map(|i| format!("{}\n", i)).collect();
+        }
+    }
+}
+
+fn plant(rng: &mut Rng, size: usize) -> Vec<Instruction> {
+    let mut program = vec![Instruction::Nop(0), Instruction::Nop(rng.range(-50, 50))];
+    let mut executed = vec![0, 1];
+    while program.len() < size {
+        let pc = program.len();
+        executed.push(pc);
+        let remaining = size - pc;
+        match rng.below(4) {
+            0 if remaining > 2 => {
+                let skip = rng.range(2, 4.min(remaining as i64));
+                program.push(Instruction::Jmp(skip));
+                for _ in 1..skip {
+                    // Never executed, so anything goes
+                    program.push(match rng.below(3) {
+                        0 => Instruction::Acc(rng.range(-50, 50)),
+                        1 => Instruction::Jmp(rng.range(-(program.len() as i64), -1)),
+                        _ => Instruction::Nop(rng.range(-50, 50)),
+                    });
+                }
+            },
+            1 | 2 => program.push(Instruction::Acc(rng.range(-50, 50))),
+            _ => program.push(Instruction::Nop(rng.range(-50, 50))),
+        }
+    }
+
+    let nops: Vec<usize> = executed.iter()
+        .copied()
+        .filter(|pc| *pc > 0 && matches!(program[*pc], Instruction::Nop(_)))
+        .collect();
+    let broken = *rng.pick(&nops);
+    let earlier: Vec<usize> = executed.iter().copied().filter(|pc| *pc < broken).collect();
+    let target = *rng.pick(&earlier);
+    program[broken] = Instruction::Jmp(target as i64 - broken as i64);
+
+    // A nop before the broken jmp that would jump past it as a jmp is a second fix, so keep
+    // those jumps inside the loop
+    for &pc in &earlier {
+        if let Instruction::Nop(operand) = program[pc] {
+            if pc as i64 + operand < 0 || pc as i64 + operand > broken as i64 {
+                program[pc] = Instruction::Nop(rng.range(-(pc as i64), (broken - pc) as i64));
+            }
+        }
+    }
+    program
+}
+
+// Every single jmp/nop swap that lets a looping program run to the end, with its final
+// accumulator. Works out once which instructions run on to the end and what they add, so each
+// swap along the loop is checked without running the program again.
+fn fixes(program: &[Instruction]) -> Vec<(usize, i64)> {
+    let end = program.len();
+    let next = |pc: usize, instruction: Instruction| -> Option<usize> {
+        let target = pc as i64 + match instruction {
+            Instruction::Jmp(operand) => operand,
+            _ => 1,
+        };
+        if target >= 0 && target <= end as i64 { Some(target as usize) } else { None }
+    };
+
+    // What each instruction adds on its way to the end, for those that get there
+    let mut sources = vec![Vec::new(); end + 1];
+    for (pc, instruction) in program.iter().enumerate() {
+        if let Some(target) = next(pc, *instruction) {
+            sources[target].push(pc);
+        }
+    }
+    let mut to_end: Vec<Option<i64>> = vec![None; end + 1];
+    to_end[end] = Some(0);
+    let mut queue = vec![end];
+    while let Some(target) = queue.pop() {
+        for &pc in &sources[target] {
+            let added = match program[pc] {
+                Instruction::Acc(operand) => operand,
+                _ => 0,
+            };
+            to_end[pc] = to_end[target].map(|acc| acc + added);
+            queue.push(pc);
+        }
+    }
+
+    // A loop never reaches the end, so the patched path from a swap can't come back through it
+    let mut visited = vec![false; end];
+    let (mut pc, mut acc) = (0, 0);
+    let mut fixes = Vec::new();
+    while pc < end && !visited[pc] {
+        visited[pc] = true;
+        let swapped = match program[pc] {
+            Instruction::Jmp(operand) => Some(Instruction::Nop(operand)),
+            Instruction::Nop(operand) => Some(Instruction::Jmp(operand)),
+            Instruction::Acc(operand) => {
+                acc += operand;
+                None
+            },
+        };
+        if let Some(rest) = swapped.and_then(|i| next(pc, i)).and_then(|target| to_end[target]) {
+            fixes.push((pc, acc + rest));
+        }
+        match next(pc, program[pc]) {
+            Some(target) => pc = target,
+            None => break,
+        }
+    }
+    fixes
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct CPU {
+    pub acc: i64,
+    pub pc: i64,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Copy, Clone)]
+struct Patch {
+    find: Instruction,
+    replace: Instruction,
+    start_at: usize,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Copy, Clone, PartialOrd, PartialEq, Debug)]
+pub enum Instruction {
+    Acc(i64),
+    Jmp(i64),
+    Nop(i64),
+}
+
+#[derive(Copy, Clone,Eq, PartialEq, Debug)]
+pub enum TerminationReason {
+    Loop,
+    Complete,
+}
+
+impl Instruction {
+    pub fn operand(&self) -> i64 {
+        match self {
+            Self::Acc(operand) => *operand,
+            Self::Jmp(operand) => *operand,
+            Self::Nop(operand) => *operand,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Acc(operand) => write!(f, "acc {:+}", operand),
+            Self::Jmp(operand) => write!(f, "jmp {:+}", operand),
+            Self::Nop(operand) => write!(f, "nop {:+}", operand),
+        }
+    }
+}
+
+/// A line that isn't an instruction, e.g. an unknown operation or an operand that doesn't fit
+/// in an `i64`
+#[derive(PartialEq, Debug)]
+pub struct InstructionError(String);
+
+impl fmt::Display for InstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected 'acc|jmp|nop' and a signed number, got '{}'", self.0)
+    }
+}
+
+impl Error for InstructionError {}
+
+impl FromStr for Instruction {
+    type Err = InstructionError;
+
+    // The sign is parsed with the digits, so -9223372036854775808 fits
+    fn from_str(s: &str) -> Result<Instruction, Self::Err> {
+        let parser = Regex::new(r"^(.+?) (\+|-)(\d+)").unwrap();
+        let error = || InstructionError(s.to_string());
+        let captures = parser.captures(s).ok_or_else(error)?;
+
+        let instr = captures.get(1).unwrap().as_str();
+        let sign = captures.get(2).unwrap().as_str();
+        let digits = captures.get(3).unwrap().as_str();
+        let operand: i64 = format!("{}{}", sign, digits).parse().map_err(|_| error())?;
+
+        match instr {
+            "acc" => Ok(Instruction::Acc(operand)),
+            "jmp" => Ok(Instruction::Jmp(operand)),
+            "nop" => Ok(Instruction::Nop(operand)),
+            _ => Err(error()),
+        }
+    }
+}
+
+/// Both parts as a `Puzzle`, and the machine that runs the program
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+pub struct Puzzle1 {
+    cpu: CPU,
+    program: Vec<Instruction>,
+
+    patch: Option<Patch>
+}
+
+impl Puzzle1 {
+    pub fn from_program(program: Vec<Instruction>) -> Puzzle1 {
+        Puzzle1 { program, ..Default::default() }
+    }
+
+    pub fn cpu(&self) -> &CPU {
+        &self.cpu
+    }
+
+    fn compile_and_add_line(&mut self, line: &str) {
+        self.program.push(line.parse::<Instruction>().unwrap());
+    }
+
+    fn decode(&self) -> Instruction {
+        let original_inst = self.program[self.cpu.pc as usize];
+        match self.patch {
+            Some(p) if p.start_at == self.cpu.pc as usize => {
+                match p.replace {
+                    Instruction::Acc(_) => Instruction::Acc(original_inst.operand()),
+                    Instruction::Jmp(_) => Instruction::Jmp(original_inst.operand()),
+                    Instruction::Nop(_) => Instruction::Nop(original_inst.operand()),
+                }
+            },
+            _ => original_inst,
+        }
+    }
+
+    pub fn run_till_loop(&mut self) -> TerminationReason {
+        let mut visited_memory = Vec::new();
+        while !visited_memory.contains(&self.cpu.pc) {
+            let pc = self.cpu.pc as usize;
+            let pc_change = match self.decode() {
+                Instruction::Acc(operand) => {
+                    self.cpu.acc += operand;
+                    1
+                },
+                Instruction::Jmp(operand) => operand,
+                Instruction::Nop(_) => 1,
+            };
+            visited_memory.push(pc as i64);
+            self.cpu.pc += pc_change;
+
+            if self.cpu.pc as usize == self.program.len() {
+                observer::counter("instructions_executed", visited_memory.len() as i64);
+                return TerminationReason::Complete;
+            }
+        }
+
+        observer::counter("instructions_executed", visited_memory.len() as i64);
+        TerminationReason::Loop
+    }
+
+    pub fn reset(&mut self) {
+        self.cpu.acc = 0;
+        self.cpu.pc = 0;
+        self.patch = None;
+    }
+
+    fn patch(&mut self, patch: &Patch) -> Option<usize> {
+        for mem_i in patch.start_at..self.program.len() {
+            let mem_instr = self.program[mem_i];
+            if discriminant(&mem_instr) == discriminant(&patch.find) {
+                self.patch = Some(Patch { find: patch.find, replace: patch.replace, start_at: mem_i });
+                return Some(mem_i);
+            }
+        }
+
+        None
+    }
+
+    fn try_patch(&mut self, patch: &mut Patch) -> TerminationReason {
+        self.reset();
+        let mut replaced_at = self.patch(patch);
+        let mut attempts = 0;
+        while replaced_at.is_some() {
+            attempts += 1;
+            observer::counter("patch_attempts", attempts);
+            patch.start_at = replaced_at.unwrap() + 1;
+            match self.run_till_loop() {
+                TerminationReason::Complete => return TerminationReason::Complete,
+                TerminationReason::Loop => {
+                    self.reset();
+                    replaced_at = self.patch(patch)
+                },
+            };
+        }
+        TerminationReason::Loop
+    }
+
+    pub fn try_to_correct(&mut self) -> TerminationReason {
+        let mut patch_1 = Patch { find: Instruction::Jmp(0), replace: Instruction::Nop(0), start_at: 0 };
+        let mut patch_2 = Patch { find: Instruction::Nop(0), replace: Instruction::Jmp(0), start_at: 0 };
+
+        match self.try_patch(&mut patch_1) {
+            TerminationReason::Loop => self.try_patch(&mut patch_2),
+            TerminationReason::Complete => TerminationReason::Complete,
+        }
+    }
+}
+
+impl Puzzle for Puzzle1 {
+    type ParsedLine = String;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        self.compile_and_add_line(&item);
+    }
+
+    fn final_result(&mut self) -> String {
+        self.run_till_loop();
+        let part_a = self.cpu.acc;
+        let part_b = match self.try_to_correct() {
+            TerminationReason::Complete => self.cpu.acc,
+            TerminationReason::Loop => panic!(),
+        };
+
+        format!("a: {}; b: {}", part_a, part_b)
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+
+    #[test]
+    fn example_1() {
+        let input = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6".to_string();
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+        subject.reset();
+        subject.run_till_loop();
+
+        assert_eq!(9, subject.program.len());
+        assert_eq!(5, subject.cpu.acc);
+    }
+
+    #[test]
+    fn example_2() {
+        let input = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6".to_string();
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+        let result = subject.try_to_correct();
+
+        assert_eq!(result, TerminationReason::Complete);
+        assert_eq!(8, subject.cpu.acc);
+    }
+
+    #[test]
+    fn solve() {
+        let input = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+        assert_eq!(9, parse(input).len());
+        assert_eq!(5, solve_a(input));
+        assert_eq!(Some(8), solve_b(input));
+        assert_eq!(None, solve_b("jmp +0\njmp -1"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn snapshot_golden() {
+        let input = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6".to_string();
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+
+        assert_eq!(include_str!("../golden/example.solved.json").trim_end(), subject.snapshot().unwrap());
+    }
+
+    #[test]
+    fn generated_input() {
+        for seed in 0..5 {
+            let input = generate::generate(generate_input, seed, 200);
+            assert!(schema().lint(&input).is_empty());
+
+            let fixes = fixes(&parse(&input));
+            assert_eq!(1, fixes.len());
+
+            let mut subject: Puzzle1 = Default::default();
+            subject.run_with_input(input);
+            assert_eq!(200, subject.program.len());
+            assert_eq!(TerminationReason::Complete, subject.try_to_correct());
+            assert_eq!(fixes[0].1, subject.cpu.acc);
+        }
+    }
+
+    // Accumulator when the program runs to the end, or None if it loops or jumps outside itself
+    fn run(program: &[Instruction]) -> Option<i64> {
+        let mut visited = vec![false; program.len()];
+        let (mut pc, mut acc) = (0i64, 0);
+        while pc != program.len() as i64 {
+            if pc < 0 || pc > program.len() as i64 || visited[pc as usize] {
+                return None;
+            }
+            visited[pc as usize] = true;
+            pc += match program[pc as usize] {
+                Instruction::Acc(operand) => {
+                    acc += operand;
+                    1
+                },
+                Instruction::Jmp(operand) => operand,
+                Instruction::Nop(_) => 1,
+            };
+        }
+        Some(acc)
+    }
+
+    // Every swap tried by running the patched program, to check `fixes` against
+    fn fixes_by_running(program: &[Instruction]) -> Vec<(usize, i64)> {
+        let mut patched = program.to_vec();
+        let mut fixes = Vec::new();
+        for (pc, instruction) in program.iter().enumerate() {
+            patched[pc] = match *instruction {
+                Instruction::Jmp(operand) => Instruction::Nop(operand),
+                Instruction::Nop(operand) => Instruction::Jmp(operand),
+                Instruction::Acc(_) => continue,
+            };
+            if let Some(acc) = run(&patched) {
+                fixes.push((pc, acc));
+            }
+            patched[pc] = *instruction;
+        }
+        fixes
+    }
+
+    #[test]
+    fn fixes_match_running() {
+        for seed in 0..20 {
+            let mut rng = Rng::new(seed);
+            let program = plant(&mut rng, 100);
+            assert_eq!(fixes_by_running(&program), fixes(&program));
+        }
+        let program = parse("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6\n");
+        assert_eq!(vec![(7, 8)], fixes(&program));
+        assert_eq!(fixes_by_running(&program), fixes(&program));
+    }
+
+    #[test]
+    fn generates_large_input() {
+        let start = std::time::Instant::now();
+        let input = generate::generate(generate_input, 8, 200_000);
+        assert_eq!(200_000, input.lines().count());
+        assert!(start.elapsed() < std::time::Duration::from_secs(20), "took {:?}", start.elapsed());
+    }
+
+    #[test]
+    fn instruction_round_trip() {
+        generate::assert_round_trip(
+            200,
+            |rng| {
+                let operand = match rng.below(20) {
+                    0 => i64::MIN,
+                    1 => i64::MAX,
+                    2 | 3 => rng.range(-i64::MAX, i64::MAX),
+                    _ => rng.range(-1000, 1000),
+                };
+                match rng.below(3) {
+                    0 => Instruction::Acc(operand),
+                    1 => Instruction::Jmp(operand),
+                    _ => Instruction::Nop(operand),
+                }
+            },
+            |i| i.to_string(),
+            |s| s.parse().unwrap(),
+        );
+
+        assert_eq!(Ok(Instruction::Acc(i64::MIN)), "acc -9223372036854775808".parse());
+        assert_eq!(Ok(Instruction::Jmp(i64::MAX)), "jmp +9223372036854775807".parse());
+        assert_eq!(Err(InstructionError(String::from("nop +9223372036854775808"))), "nop +9223372036854775808".parse::<Instruction>());
+        assert_eq!(Err(InstructionError(String::from("mul +2"))), "mul +2".parse::<Instruction>());
+        assert!("acc 5".parse::<Instruction>().is_err());
+    }
+}
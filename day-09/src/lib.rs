@@ -0,0 +1,307 @@
+//! Day 9: Encoding Error. Finds the first number that isn't the sum of two of the numbers
+//! before it, then the run of numbers that adds up to it.
+
+use common::Puzzle;
+use common::generate::Rng;
+use common::lint::Schema;
+use common::variants::Variant;
+use std::collections::{HashMap, VecDeque};
+
+/// Preamble length of the real input. The examples use 5.
+pub const PREAMBLE_LENGTH: usize = 25;
+
+/// The XMAS data, one number per line
+pub fn parse(input: &str) -> Vec<i64> {
+    input.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.trim().parse().expect("Bad number"))
+        .collect()
+}
+
+/// First number that isn't the sum of two of the `preamble_length` numbers before it
+pub fn solve_a(input: &str, preamble_length: usize) -> Option<i64> {
+    Puzzle1::from_list(parse(input), preamble_length).weak_number
+}
+
+/// Sum of the smallest and largest numbers in the contiguous run that adds up to part A's
+/// answer
+pub fn solve_b(input: &str, preamble_length: usize) -> Option<i64> {
+    let puzzle = Puzzle1::from_list(parse(input), preamble_length);
+    puzzle.weak_number.map(|_| puzzle.find_encryption_weakness())
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().line_pattern(r"\d+")
+}
+
+/// Each number after the 25 number preamble is a sum of two of the previous 25, up to the one
+/// that isn't: the sum of an earlier contiguous run. Every entry has to be at least the sum of
+/// two from the window before, so they grow exponentially and the valid part is kept short;
+/// anything after the weak number is just random padding.
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    const PREAMBLE: usize = 25;
+    let weak_at = (PREAMBLE + size / 2).clamp(PREAMBLE + 2, PREAMBLE + 400);
+
+    let mut list: Vec<i64> = Vec::new();
+    while list.len() < PREAMBLE {
+        let n = rng.range(1, 100);
+        if !list.contains(&n) {
+            list.push(n);
+        }
+    }
+    while list.len() < weak_at {
+        let window = &list[list.len() - PREAMBLE..];
+        let a = rng.below(PREAMBLE);
+        let b = (a + 1 + rng.below(PREAMBLE - 1)) % PREAMBLE;
+        let n = window[a] + window[b];
+        list.push(n);
+    }
+
+    let window = &list[weak_at - PREAMBLE..];
+    let is_sum = |n: i64| window.iter().enumerate()
+        .any(|(i, a)| window[i + 1..].iter().any(|b| a + b == n));
+    let weak = loop {
+        let start = rng.below(weak_at - 2);
+        let end = (start + rng.range(2, 5) as usize).min(weak_at);
+        let sum: i64 = list[start..end].iter().sum();
+        if !is_sum(sum) {
+            break sum;
+        }
+    };
+    list.push(weak);
+
+    let max = *list.iter().max().unwrap();
+    while list.len() < size {
+        list.push(rng.range(1, max));
+    }
+
+    list.iter().map(|n| format!("{}\n", n)).collect()
+}
+
+// Windowed checks validity with a count of the buffer's values and finds the weak range with
+// a sliding window, which relies on every number being positive
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default, Clone, Copy)]
+enum Strategy {
+    #[default]
+    Nested,
+    Windowed,
+}
+
+/// Both parts as a `Puzzle`, with a variant per strategy
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+pub struct Puzzle1 {
+    buffer: VecDeque<i64>,
+    buffer_counts: HashMap<i64, usize>,
+    list: Vec<i64>,
+    weak_number: Option<i64>,
+    preamble_length: usize,
+    strategy: Strategy,
+}
+
+impl Puzzle1 {
+    pub fn new(preamble_length: usize) -> Puzzle1 {
+        Puzzle1 { preamble_length, ..Default::default() }
+    }
+
+    fn from_list(list: Vec<i64>, preamble_length: usize) -> Puzzle1 {
+        let mut puzzle = Puzzle1 { preamble_length, strategy: Strategy::Windowed, ..Default::default() };
+        for n in list {
+            puzzle.process_item(n);
+        }
+        puzzle
+    }
+
+    pub fn variants() -> Vec<Variant<Puzzle1>> {
+        vec![
+            Variant { name: "nested", puzzle: || Puzzle1 { preamble_length: PREAMBLE_LENGTH, strategy: Strategy::Nested, ..Default::default() } },
+            Variant { name: "windowed", puzzle: || Puzzle1 { preamble_length: PREAMBLE_LENGTH, strategy: Strategy::Windowed, ..Default::default() } },
+        ]
+    }
+
+    fn is_valid(&self, n: i64) -> bool {
+        match self.strategy {
+            Strategy::Nested => self.is_valid_nested(n),
+            Strategy::Windowed => self.is_valid_counted(n),
+        }
+    }
+
+    fn is_valid_nested(&self, n: i64) -> bool {
+        for a_i in 0..(self.buffer.len() - 1) {
+            let a = self.buffer.get(a_i).unwrap();
+            for b_i in (a_i + 1)..self.buffer.len() {
+                let b = self.buffer.get(b_i).unwrap();
+                if (a + b) == n {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn is_valid_counted(&self, n: i64) -> bool {
+        self.buffer.iter().any(|a| {
+            let b = n - a;
+            let needed = if b == *a { 2 } else { 1 };
+            self.buffer_counts.get(&b).copied().unwrap_or(0) >= needed
+        })
+    }
+
+    fn push_buffer(&mut self, n: i64) {
+        self.buffer.push_back(n);
+        *self.buffer_counts.entry(n).or_insert(0) += 1;
+    }
+
+    fn pop_buffer(&mut self) {
+        if let Some(n) = self.buffer.pop_front() {
+            let count = self.buffer_counts.get_mut(&n).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                self.buffer_counts.remove(&n);
+            }
+        }
+    }
+
+    fn check_for_weakness(&mut self, n: i64) {
+        if self.weak_number.is_some() {
+            // Already have it
+            return
+        }
+
+        if self.buffer.len() < self.preamble_length {
+            // still reading preamble
+            self.push_buffer(n);
+        } else if self.is_valid(n) {
+            self.pop_buffer();
+            self.push_buffer(n);
+        } else {
+            // Found the odd one out
+            self.weak_number = Some(n);
+        }
+    }
+
+    fn find_weak_range(&self) -> &[i64] {
+        match self.strategy {
+            Strategy::Nested => self.find_weak_range_nested(),
+            Strategy::Windowed => self.find_weak_range_windowed(),
+        }
+    }
+
+    fn find_weak_range_nested(&self) -> &[i64] {
+        let target = self.weak_number.unwrap();
+        for a_i in 0..(self.list.len() - 1) {
+            for b_i in (a_i + 1)..self.list.len() {
+                let slice = &self.list[a_i..=b_i];
+                if slice.iter().sum::<i64>() == target {
+                    return slice;
+                }
+            }
+        }
+
+        panic!()
+    }
+
+    fn find_weak_range_windowed(&self) -> &[i64] {
+        let target = self.weak_number.unwrap();
+        let mut low = 0;
+        let mut sum = 0;
+        for high in 0..self.list.len() {
+            sum += self.list[high];
+            while sum > target && low < high {
+                sum -= self.list[low];
+                low += 1;
+            }
+            if sum == target && low < high {
+                return &self.list[low..=high];
+            }
+        }
+
+        panic!()
+    }
+
+    fn find_encryption_weakness(&self) -> i64 {
+        let weakness = self.find_weak_range();
+        let min = weakness.iter().min().unwrap();
+        let max = weakness.iter().max().unwrap();
+
+        min + max
+    }
+}
+
+impl Puzzle for Puzzle1 {
+    type ParsedLine = i64;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        self.list.push(item);
+        self.check_for_weakness(item);
+    }
+
+    fn final_result(&mut self) -> String {
+        let weak_num = self.weak_number.unwrap();
+        let weak_enc = self.find_encryption_weakness();
+
+        format!("weak number: {}; weak encryption: {}", weak_num, weak_enc)
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+    use common::variants;
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
+    fn example_1() {
+        let input = "35\n20\n15\n25\n47\n40\n62\n55\n65\n95\n102\n117\n150\n182\n127\n219\n299\n277\n309\n576".to_string();
+        let mut subject: Puzzle1 = Default::default();
+        subject.preamble_length = 5;
+        subject.run_with_input(input);
+
+        assert!(subject.weak_number.is_some());
+        assert_eq!(127, subject.weak_number.unwrap());
+        assert_eq!(62, subject.find_encryption_weakness());
+    }
+
+    #[test]
+    fn solve() {
+        let input = "35\n20\n15\n25\n47\n40\n62\n55\n65\n95\n102\n117\n150\n182\n127\n219\n299\n277\n309\n576";
+        assert_eq!(20, parse(input).len());
+        assert_eq!(Some(127), solve_a(input, 5));
+        assert_eq!(Some(62), solve_b(input, 5));
+        assert_eq!(None, solve_a("1\n2\n3\n", 2));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 1000);
+        assert!(schema().lint(&input).is_empty());
+
+        let mut subject = Puzzle1 { preamble_length: 25, ..Default::default() };
+        subject.run_with_input(input);
+        assert_eq!(1000, subject.list.len());
+        assert!(subject.weak_number.is_some());
+    }
+
+    #[test]
+    fn variants_agree() {
+        for seed in 0..3 {
+            let input = generate::generate(generate_input, seed, 300);
+            variants::assert_agree(&Puzzle1::variants(), &input);
+        }
+
+        variants::assert_agree(&Puzzle1::variants(), &generate::generate(generate_input, 9, 1000));
+
+        if let Some(input) = variants::real_input() {
+            variants::assert_agree(&Puzzle1::variants(), &input);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Day 10: Adapter Array. Chains every joltage adaptor in the bag between the outlet and the
+//! device.
+
+use common::Puzzle;
+use common::generate::Rng;
+use common::lint::Schema;
+use std::collections::HashMap;
+
+/// Adaptor ratings, one per line
+pub fn parse(input: &str) -> Vec<i64> {
+    input.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.trim().parse().expect("Bad rating"))
+        .collect()
+}
+
+/// Number of 1 jolt gaps times the number of 3 jolt gaps when every adaptor is used
+pub fn solve_a(input: &str) -> i64 {
+    Puzzle1 { adaptors: parse(input) }.part_a()
+}
+
+/// Number of distinct ways to connect the outlet to the device, or None if that doesn't fit
+/// in a u128
+pub fn solve_b(input: &str) -> Option<u128> {
+    Puzzle1 { adaptors: parse(input) }.part_b()
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().line_pattern(r"\d+")
+}
+
+/// A single chain of adaptors with 1 and 3 jolt gaps, starting with a 1 jolt gap
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut adaptors = Vec::new();
+    let mut rating = 0;
+    for i in 0..size.max(1) {
+        rating += if i == 0 || rng.chance(0.6) { 1 } else { 3 };
+        adaptors.push(rating);
+    }
+    rng.shuffle(&mut adaptors);
+
+    adaptors.iter().map(|n| format!("{}\n", n)).collect()
+}
+
+/// Both parts as a `Puzzle`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+pub struct Puzzle1 {
+    adaptors: Vec<i64>,
+}
+
+impl Puzzle1 {
+    fn part_a(&mut self) -> i64 {
+        self.adaptors.sort();
+
+        let mut gaps: HashMap<i64,i64> = HashMap::new();
+        let mut previous_rating = 0;
+        for rating in self.adaptors.iter() {
+            let gap = rating - previous_rating;
+            *gaps.entry(gap).or_insert(0) += 1;
+            previous_rating = *rating;
+        }
+
+        // Device
+        *gaps.entry(3).or_insert(0) += 1;
+
+        let one_jolt_diffs = gaps[&1];
+        let three_jolt_diffs = gaps[&3];
+
+        one_jolt_diffs * three_jolt_diffs
+    }
+
+    // Ways to reach each rating are the sum of the ways to reach the three ratings below it.
+    // Long runs of 1 jolt gaps grow that exponentially, hence the overflow check.
+    fn part_b(&mut self) -> Option<u128> {
+        self.adaptors.sort();
+
+        let mut ways: HashMap<i64, u128> = HashMap::new();
+        ways.insert(0, 1);
+        for rating in self.adaptors.iter() {
+            let mut reaching: u128 = 0;
+            for gap in 1..=3 {
+                reaching = reaching.checked_add(ways.get(&(rating - gap)).copied().unwrap_or(0))?;
+            }
+            ways.insert(*rating, reaching);
+        }
+
+        Some(self.adaptors.last().map_or(1, |highest| ways[highest]))
+    }
+}
+
+impl Puzzle for Puzzle1 {
+    type ParsedLine = i64;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        self.adaptors.push(item);
+    }
+
+    fn final_result(&mut self) -> String {
+        let part_a = self.part_a();
+        let part_b = self.part_b().map_or("too many to count".to_string(), |ways| ways.to_string());
+
+        format!("a: {}; b: {}", part_a, part_b)
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+
+    #[test]
+    fn example_1() {
+        let input = "16\n10\n15\n5\n1\n11\n7\n19\n6\n12\n4".to_string();
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+
+        assert_eq!(11, subject.adaptors.len());
+        assert_eq!(7 * 5, subject.part_a());
+        assert_eq!(Some(8), subject.part_b());
+    }
+
+    #[test]
+    fn example_2() {
+        let input = "28\n33\n18\n42\n31\n14\n46\n20\n48\n47\n24\n23\n49\n45\n19\n38\n39\n11\n1\n32\n25\n35\n8\n17\n7\n9\n4\n2\n34\n10\n3".to_string();
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+
+        assert_eq!(31, subject.adaptors.len());
+        assert_eq!(22 * 10, subject.part_a());
+        assert_eq!(Some(19208), subject.part_b());
+    }
+
+    #[test]
+    fn solve() {
+        let input = "16\n10\n15\n5\n1\n11\n7\n19\n6\n12\n4";
+        assert_eq!(11, parse(input).len());
+        assert_eq!(35, solve_a(input));
+        assert_eq!(Some(8), solve_b(input));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 1000);
+        assert!(schema().lint(&input).is_empty());
+
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+        assert_eq!(1000, subject.adaptors.len());
+        assert!(subject.part_a() > 0);
+    }
+}
@@ -0,0 +1,288 @@
+//! Day 11: Seating System. Runs the seating rules until nobody else moves, once looking at
+//! adjacent seats and once looking at the first seat in each direction.
+
+use common::Puzzle;
+use common::generate::Rng;
+use common::lint::Schema;
+use common::observer::{self, Event};
+
+/// The seat layout, one row per line
+pub fn parse(input: &str) -> Vec<Row> {
+    input.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| Puzzle1::to_row(l.trim()))
+        .collect()
+}
+
+/// Occupied seats once stable, with people leaving when 4 or more adjacent seats are taken
+pub fn solve_a(input: &str) -> i64 {
+    Puzzle1::from_rows(parse(input)).stable_occupied(4, Perception::Close)
+}
+
+/// Occupied seats once stable, with people leaving when 5 or more visible seats are taken
+pub fn solve_b(input: &str) -> i64 {
+    Puzzle1::from_rows(parse(input)).stable_occupied(5, Perception::Far)
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().allowed_chars("L.").consistent_width()
+}
+
+/// A square seat layout, size seats wide and size rows tall
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let row: String = (0..size).map(|_| if rng.chance(0.75) { 'L' } else { '.' }).collect();
+        input.push_str(&row);
+        input.push('\n');
+    }
+    input
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum FloorState {
+    Empty,
+    Seat,
+    Person,
+    Hyperspace,
+}
+
+enum Perception {
+    Close,
+    Far,
+}
+
+pub type Row = Vec<FloorState>;
+
+/// Both parts as a `Puzzle`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+pub struct Puzzle1 {
+    rows: Vec<Row>,
+    previous: Vec<Row>,
+
+    close_occupied: i64,
+    far_occupied: i64,
+}
+
+impl Puzzle1 {
+    pub fn from_rows(rows: Vec<Row>) -> Puzzle1 {
+        Puzzle1 { rows, ..Default::default() }
+    }
+
+    // Runs a copy of the layout, leaving this one as it was
+    fn stable_occupied(&self, tolerance: usize, perception: Perception) -> i64 {
+        let mut run = Puzzle1::from_rows(self.rows.clone());
+        run.run_to_stability(tolerance, perception);
+        run.count_occupied()
+    }
+
+    fn to_row(input: &str) -> Row {
+        let mut line = Row::new();
+        for c in input.chars() {
+            let state = match c {
+                'L' => FloorState::Seat,
+                '.' => FloorState::Empty,
+                _ => panic!()
+            };
+            line.push(state);
+        }
+
+        line
+    }
+
+    fn read(&self, x: i64, y: i64) -> FloorState {
+        if y < 0 || y as usize >= self.rows.len() {
+            return FloorState::Hyperspace;
+        }
+
+        let row = &self.rows[y as usize];
+        if x < 0 || x as usize >= row.len() {
+            return FloorState::Hyperspace;
+        }
+
+        row[x as usize]
+    }
+
+    fn sample_close(&self, x: i64, y: i64) -> Vec<FloorState> {
+        let mut sample = Vec::new();
+        for y_d in -1..=1 {
+            for x_d in -1..=1 {
+                if y_d == 0 && x_d == 0 {
+                    continue;
+                }
+                let state = self.read(x + x_d, y + y_d);
+                sample.push(state);
+            }
+        }
+        sample
+    }
+
+    fn sample_directed(&self, x: i64, y: i64, d_x: i64, d_y: i64) -> FloorState {
+        let mut cur_x = x + d_x;
+        let mut cur_y = y +  d_y;
+        let mut sample = self.read(cur_x, cur_y);
+        while sample == FloorState::Empty {
+            cur_x += d_x;
+            cur_y += d_y;
+            sample = self.read(cur_x, cur_y);
+        }
+        sample
+    }
+
+    #[allow(clippy::vec_init_then_push)]
+    fn sample_far(&self, x: i64, y: i64) -> Vec<FloorState> {
+        let mut sample = Vec::new();
+
+        sample.push(self.sample_directed(x, y, 0, -1));
+        sample.push(self.sample_directed(x, y, 0, 1));
+        sample.push(self.sample_directed(x, y, -1, 0));
+        sample.push(self.sample_directed(x, y, 1, 0));
+        sample.push(self.sample_directed(x, y, 1, 1));
+        sample.push(self.sample_directed(x, y, -1, -1));
+        sample.push(self.sample_directed(x, y, 1, -1));
+        sample.push(self.sample_directed(x, y, -1, 1));
+
+        sample
+    }
+
+    fn step(&self, tolerance: usize, perception: &Perception) -> Vec<Row> {
+        let mut next_state = Vec::new();
+        for y in 0..self.rows.len() as i64 {
+            let row = &self.rows[y as usize];
+            let mut new_row = Row::new();
+            for x in 0..row.len() as i64 {
+                let sample = match perception {
+                    Perception::Close => self.sample_close(x, y),
+                    Perception::Far => self.sample_far(x, y),
+                };
+                let next_seat_state = match self.read(x, y) {
+                    FloorState::Hyperspace => panic!(),
+                    FloorState::Empty => FloorState::Empty,
+                    FloorState::Seat => {
+                        if sample.iter().filter(|&f| *f == FloorState::Person).count() == 0 {
+                            FloorState::Person
+                        } else {
+                            FloorState::Seat
+                        }
+                    },
+                    FloorState::Person => {
+                        if sample.iter().filter(|&f| *f == FloorState::Person).count() >= tolerance {
+                            FloorState::Seat
+                        } else {
+                            FloorState::Person
+                        }
+                    },
+                };
+                new_row.push(next_seat_state);
+            }
+            next_state.push(new_row);
+        }
+
+        next_state
+    }
+
+    fn step_and_swap(&mut self, tolerance: usize, perception: &Perception) {
+        let new_state = self.step(tolerance, perception);
+        self.previous = self.rows.clone();
+        self.rows = new_state;
+    }
+
+    fn print(rows: &[Row]) -> String {
+        let mut out = String::new();
+        for row in rows {
+            for place in row {
+                let str = match place {
+                    FloorState::Empty => ".",
+                    FloorState::Seat => "L",
+                    FloorState::Person => "#",
+                    FloorState::Hyperspace => panic!(),
+                };
+                out.push_str(str);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn print_rows(&self) -> String {
+        Puzzle1::print(&self.rows)
+    }
+
+    fn run_to_stability(&mut self, tolerance: usize, perception: Perception) {
+        let mut step = 0;
+        while self.rows != self.previous {
+            self.step_and_swap(tolerance, &perception);
+            step += 1;
+            observer::emit(|| Event::SimulationStep { step, state: Some(self.print_rows()) });
+            observer::emit(|| Event::Counter { name: "occupied", value: self.count_occupied() });
+        }
+    }
+
+    fn count_occupied(&self) -> i64 {
+        let mut count = 0;
+        for row in &self.rows {
+            count += row.iter().filter(|&f| *f == FloorState::Person).count();
+        }
+        count as i64
+    }
+}
+
+impl Puzzle for Puzzle1 {
+    type ParsedLine = String;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        let line = Puzzle1::to_row(&item);
+        self.rows.push(line);
+    }
+
+    fn final_result(&mut self) -> String {
+        self.close_occupied = self.stable_occupied(4, Perception::Close);
+        self.far_occupied = self.stable_occupied(5, Perception::Far);
+
+        format!("close: {}; far: {}", self.close_occupied, self.far_occupied)
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+
+    #[test]
+    fn example_1() {
+        let input = "L.LL.LL.LL\nLLLLLLL.LL\nL.L.L..L..\nLLLL.LL.LL\nL.LL.LL.LL\nL.LLLLL.LL\n..L.L.....\nLLLLLLLLLL\nL.LLLLLL.L\nL.LLLLL.LL".to_string();
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+
+        assert_eq!(37, subject.close_occupied);
+        assert_eq!(26, subject.far_occupied);
+    }
+
+    #[test]
+    fn solve() {
+        let input = "L.LL.LL.LL\nLLLLLLL.LL\nL.L.L..L..\nLLLL.LL.LL\nL.LL.LL.LL\nL.LLLLL.LL\n..L.L.....\nLLLLLLLLLL\nL.LLLLLL.L\nL.LLLLL.LL";
+        assert_eq!(10, parse(input).len());
+        assert_eq!(37, solve_a(input));
+        assert_eq!(26, solve_b(input));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 30);
+        assert!(schema().lint(&input).is_empty());
+
+        let mut subject: Puzzle1 = Default::default();
+        subject.run_with_input(input);
+        assert_eq!(30, subject.rows.len());
+        assert!(subject.close_occupied > 0);
+        assert!(subject.far_occupied > 0);
+    }
+}
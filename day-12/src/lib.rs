@@ -0,0 +1,413 @@
+//! Day 12: Rain Risk. Follows the ferry's navigation instructions, first as moves of the ship
+//! and then as moves of a waypoint around it.
+
+use common::Puzzle;
+use common::generate::Rng;
+use common::lint::Schema;
+use regex::Regex;
+use std::fmt;
+
+/// The navigation instructions, one per line
+pub fn parse(input: &str) -> Vec<Command> {
+    input.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| Puzzle1::to_command(l.trim().to_string()))
+        .collect()
+}
+
+/// Manhattan distance travelled when the commands move the ship
+pub fn solve_a(input: &str) -> i32 {
+    let mut ship = Puzzle1::new();
+    for command in parse(input) {
+        ship.execute(command);
+    }
+    ship.manhattan_distance()
+}
+
+/// Manhattan distance travelled when the commands move the waypoint
+pub fn solve_b(input: &str) -> i32 {
+    let mut ship = Puzzle2::new();
+    for command in parse(input) {
+        ship.execute(command);
+    }
+    ship.manhattan_distance()
+}
+
+/// Shape of a valid input, checked by the `lint` command
+pub fn schema() -> Schema {
+    Schema::new().line_pattern(r"[NSEWF]\d+|[LR](90|180|270)")
+}
+
+/// Turns are always a multiple of 90 degrees, the only angles the ship can sail at
+pub fn generate_input(rng: &mut Rng, size: usize) -> String {
+    let moves: [fn(i32) -> Command; 5] = [Command::North, Command::South, Command::East, Command::West, Command::Forward];
+    let mut input = String::new();
+    for _ in 0..size {
+        let command = match rng.below(7) {
+            0 => Command::RotateLeft(*rng.pick(&[90, 180, 270])),
+            1 => Command::RotateRight(*rng.pick(&[90, 180, 270])),
+            n => moves[n - 2](rng.range(1, 100) as i32),
+        };
+        input.push_str(&command.to_string());
+        input.push('\n');
+    }
+    input
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Command {
+    North(i32),
+    South(i32),
+    East(i32),
+    West(i32),
+    RotateLeft(i32),
+    RotateRight(i32),
+    Forward(i32),
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::North(n) => write!(f, "N{}", n),
+            Command::South(n) => write!(f, "S{}", n),
+            Command::East(n) => write!(f, "E{}", n),
+            Command::West(n) => write!(f, "W{}", n),
+            Command::RotateLeft(n) => write!(f, "L{}", n),
+            Command::RotateRight(n) => write!(f, "R{}", n),
+            Command::Forward(n) => write!(f, "F{}", n),
+        }
+    }
+}
+
+/// Part A as a `Puzzle`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+pub struct Puzzle1 {
+    direction: i32,
+    x: i32,
+    y: i32,
+}
+
+impl Puzzle1 {
+    // The ship starts facing east
+    pub fn new() -> Puzzle1 {
+        Puzzle1 { direction: 90, ..Default::default() }
+    }
+
+    fn to_command(input: String) -> Command {
+        let matcher = Regex::new(r"([A-Z])(\d+)").unwrap();
+        let captures = matcher.captures(&input).unwrap();
+
+        let command: &str = captures.get(1).unwrap().as_str();
+        let quantity: i32 = captures.get(2).unwrap().as_str().parse().unwrap();
+
+        match command {
+            "N" => Command::North(quantity),
+            "S" => Command::South(quantity),
+            "E" => Command::East(quantity),
+            "W" => Command::West(quantity),
+
+            "L" => Command::RotateLeft(quantity),
+            "R" => Command::RotateRight(quantity),
+
+            "F" => Command::Forward(quantity),
+
+            _ => panic!(),
+        }
+    }
+
+    fn move_north(&mut self, quantity: i32) {
+        self.y += quantity;
+    }
+
+    fn move_south(&mut self, quantity: i32) {
+        self.y += -quantity;
+    }
+
+    fn move_east(&mut self, quantity: i32) {
+        self.x += quantity;
+    }
+
+    fn move_west(&mut self, quantity: i32) {
+        self.x += -quantity;
+    }
+
+    fn rotate_left(&mut self, quantity: i32) {
+        self.direction += -quantity;
+        self.direction %= 360;
+    }
+
+    fn rotate_right(&mut self, quantity: i32) {
+        self.direction += quantity;
+        self.direction %= 360;
+    }
+
+    fn move_forward(&mut self, quantity: i32) {
+        match self.direction {
+            0 => self.move_north(quantity),
+            90 | -270 => self.move_east(quantity),
+            180 | -180 => self.move_south(quantity),
+            270 | -90 => self.move_west(quantity),
+
+            // Our boat is like the Automan car
+            _ => panic!("Can't move with {} degrees!", self.direction),
+        };
+    }
+
+    fn execute(&mut self, command: Command) {
+        match command {
+            Command::North(n) => self.move_north(n),
+            Command::South(n) => self.move_south(n),
+            Command::East(n) => self.move_east(n),
+            Command::West(n) => self.move_west(n),
+
+            Command::RotateLeft(n) => self.rotate_left(n),
+            Command::RotateRight(n) => self.rotate_right(n),
+
+            Command::Forward(n) => self.move_forward(n),
+        };
+    }
+
+    fn manhattan_distance(&self) -> i32 {
+        self.x.abs() + self.y.abs()
+    }
+}
+
+impl Puzzle for Puzzle1 {
+    type ParsedLine = String;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        let command = Puzzle1::to_command(item);
+        self.execute(command);
+    }
+
+    fn final_result(&mut self) -> String {
+        self.manhattan_distance().to_string()
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+
+/// Part B as a `Puzzle`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Default)]
+pub struct Puzzle2 {
+    x: i32,
+    y: i32,
+
+    w_x: i32,
+    w_y: i32,
+}
+
+impl Puzzle2 {
+    // The waypoint starts 10 east and 1 north of the ship
+    pub fn new() -> Puzzle2 {
+        Puzzle2 { w_x: 10, w_y: 1, ..Default::default() }
+    }
+
+    fn move_north(&mut self, quantity: i32) {
+        self.w_y += quantity;
+    }
+
+    fn move_south(&mut self, quantity: i32) {
+        self.w_y += -quantity;
+    }
+
+    fn move_east(&mut self, quantity: i32) {
+        self.w_x += quantity;
+    }
+
+    fn move_west(&mut self, quantity: i32) {
+        self.w_x += -quantity;
+    }
+
+    fn rotate_ccw(&mut self, direction: i32) {
+        match direction {
+            // 0 => ,
+            90 | -270 => {
+                let tmp_y = self.w_y;
+                self.w_y = self.w_x;
+                self.w_x = -tmp_y;
+            },
+            180 | -180 => {
+                self.w_y = -self.w_y;
+                self.w_x = -self.w_x;
+            },
+            270 | -90 => {
+                let tmp_y = self.w_y;
+                self.w_y = -self.w_x;
+                self.w_x = tmp_y;
+            },
+
+            // Our boat is like the Automan car
+            _ => panic!("Can't move with {} degrees!", direction),
+        };
+    }
+
+    fn rotate_cw(&mut self, direction: i32) {
+        self.rotate_ccw(-direction);
+    }
+
+    fn move_forward(&mut self, quantity: i32) {
+        self.x += self.w_x * quantity;
+        self.y += self.w_y * quantity;
+    }
+
+    fn execute(&mut self, command: Command) {
+        match command {
+            Command::North(n) => self.move_north(n),
+            Command::South(n) => self.move_south(n),
+            Command::East(n) => self.move_east(n),
+            Command::West(n) => self.move_west(n),
+
+            Command::RotateLeft(n) => self.rotate_ccw(n),
+            Command::RotateRight(n) => self.rotate_cw(n),
+
+            Command::Forward(n) => self.move_forward(n),
+        };
+    }
+
+    fn manhattan_distance(&self) -> i32 {
+        self.x.abs() + self.y.abs()
+    }
+}
+
+impl Puzzle for Puzzle2 {
+    type ParsedLine = String;
+
+    fn process_item(&mut self, item: Self::ParsedLine) {
+        let command = Puzzle1::to_command(item);
+        self.execute(command);
+    }
+
+    fn final_result(&mut self) -> String {
+        self.manhattan_distance().to_string()
+    }
+
+    #[cfg(feature = "serde")]
+    fn snapshot(&self) -> Option<String> {
+        Some(common::snapshot::to_json(self))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::generate;
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
+    fn example_1() {
+        let input = "F10\nN3\nF7\nR90\nF11".to_string();
+        let mut subject: Puzzle1 = Default::default();
+        subject.direction = 90;
+        subject.run_with_input(input);
+
+        assert_eq!(25, subject.manhattan_distance());
+    }
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
+    fn example_2() {
+        let input = "F10\nN3\nF7\nR90\nF11".to_string();
+        let mut subject: Puzzle2 = Default::default();
+        subject.w_x = 10;
+        subject.w_y = 1;
+        subject.run_with_input(input);
+
+        assert_eq!(286, subject.manhattan_distance());
+    }
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
+    fn example_3() {
+        let input = "F10".to_string();
+        let mut subject: Puzzle2 = Default::default();
+        subject.w_x = 10;
+        subject.w_y = 1;
+        subject.run_with_input(input);
+
+        assert_eq!(10, subject.w_x);
+        assert_eq!(1, subject.w_y);
+        assert_eq!(100, subject.x);
+        assert_eq!(10, subject.y);
+    }
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
+    fn example_4() {
+        let input = "R90\nF10".to_string();
+        let mut subject: Puzzle2 = Default::default();
+        subject.w_x = 10;
+        subject.w_y = 1;
+        subject.run_with_input(input);
+
+        assert_eq!(1, subject.w_x);
+        assert_eq!(-10, subject.w_y);
+        assert_eq!(10, subject.x);
+        assert_eq!(-100, subject.y);
+    }
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
+    fn example_5() {
+        let input = "L90\nF10".to_string();
+        let mut subject: Puzzle2 = Default::default();
+        subject.w_x = 10;
+        subject.w_y = 1;
+        subject.run_with_input(input);
+
+        assert_eq!(-1, subject.w_x);
+        assert_eq!(10, subject.w_y);
+        assert_eq!(-10, subject.x);
+        assert_eq!(100, subject.y);
+    }
+
+    #[test]
+    fn solve() {
+        let input = "F10\nN3\nF7\nR90\nF11";
+        assert_eq!(5, parse(input).len());
+        assert_eq!(25, solve_a(input));
+        assert_eq!(286, solve_b(input));
+    }
+
+    #[test]
+    fn generated_input() {
+        let input = generate::generate(generate_input, 3, 200);
+        assert!(schema().lint(&input).is_empty());
+
+        let mut a = Puzzle1 { direction: 90, ..Default::default() };
+        a.run_with_input(input.clone());
+        let mut b = Puzzle2 { w_x: 10, w_y: 1, ..Default::default() };
+        b.run_with_input(input);
+        assert!(a.manhattan_distance() > 0);
+        assert!(b.manhattan_distance() > 0);
+    }
+
+    #[test]
+    fn command_round_trip() {
+        generate::assert_round_trip(
+            200,
+            |rng| {
+                let n = if rng.chance(0.1) { rng.range(0, i32::MAX as i64) } else { rng.range(0, 360) } as i32;
+                match rng.below(7) {
+                    0 => Command::North(n),
+                    1 => Command::South(n),
+                    2 => Command::East(n),
+                    3 => Command::West(n),
+                    4 => Command::RotateLeft(n),
+                    5 => Command::RotateRight(n),
+                    _ => Command::Forward(n),
+                }
+            },
+            |c| c.to_string(),
+            |s| Puzzle1::to_command(s.to_string()),
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use common::{args, fmt_dur, fmt_table};
+use common::source;
+use serde_json::Value;
+use crate::registry::{self, plain, Day, Options};
+
+// Sits next to the input it checks, e.g. `alice.txt` and `alice.expected.json`, and holds
+// the answers as JSON, e.g. `{ "a": 1928, "b": 1319 }`. Parts left out aren't checked.
+const EXPECTED_SUFFIX: &str = ".expected.json";
+
+pub enum Check {
+    Unchecked,
+    Matches,
+    // Parts whose answer isn't the expected one
+    Differs(Vec<String>),
+    Invalid(String),
+}
+
+pub enum Outcome {
+    Solved { answers: Value, run: Duration, check: Check },
+    Panicked(String),
+}
+
+pub struct Row {
+    pub name: String,
+    pub outcome: Outcome,
+}
+
+impl Row {
+    pub fn passed(&self) -> bool {
+        matches!(self.outcome, Outcome::Solved { check: Check::Unchecked, .. } | Outcome::Solved { check: Check::Matches, .. })
+    }
+}
+
+// `aoc run --day N --inputs dir/ [--preamble N]`
+pub fn run() {
+    let day = args::value("--day").expect("Batch mode needs --day");
+    let day = registry::find(day.parse().expect("Bad --day")).unwrap_or_else(|| panic!("No puzzle for day {}", day));
+    let dir = args::value("--inputs").expect("Batch mode needs --inputs");
+
+    // Panics are reported in the table, so keep the default hook from printing them as well
+    panic::set_hook(Box::new(|_| {}));
+    let rows = run_dir(&day, Path::new(&dir), &Options::from_args()).unwrap_or_else(|e| panic!("Failed to read {}: {}", dir, e));
+    let _ = panic::take_hook();
+
+    println!("Day {}: {}", day.day, day.title);
+    print!("{}", table(&rows));
+    let failed = rows.iter().filter(|r| !r.passed()).count();
+    println!("{} inputs, {} failed\n", rows.len(), failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+// Every file in the directory other than the expected answers, in name order
+pub fn run_dir(day: &Day, dir: &Path, options: &Options) -> io::Result<Vec<Row>> {
+    let mut inputs: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<io::Result<Vec<PathBuf>>>()?
+        .into_iter()
+        .filter(|p| p.is_file() && !p.to_string_lossy().ends_with(EXPECTED_SUFFIX))
+        .collect();
+    inputs.sort();
+
+    inputs.iter().map(|path| run_one(day, path, options)).collect()
+}
+
+fn run_one(day: &Day, path: &Path, options: &Options) -> io::Result<Row> {
+    let name = path.file_name().unwrap().to_string_lossy().to_string();
+    let input = source::read_to_string(path)?;
+    let outcome = match registry::solve(day, &input, options) {
+        Ok(solved) => {
+            let check = check(&solved.answers, &expected_path(path));
+            Outcome::Solved { answers: solved.answers, run: solved.reports.iter().map(|r| r.run).sum(), check }
+        },
+        Err(message) => Outcome::Panicked(message),
+    };
+    Ok(Row { name, outcome })
+}
+
+// `alice.txt.gz` or `alice.txt.enc` is checked against `alice.expected.json` as well
+fn expected_path(input: &Path) -> PathBuf {
+    let input = source::plain_path(input);
+    let stem = input.file_stem().unwrap().to_string_lossy();
+    input.with_file_name(format!("{}{}", stem, EXPECTED_SUFFIX))
+}
+
+fn check(answers: &Value, expected: &Path) -> Check {
+    let expected = match fs::read_to_string(expected) {
+        Ok(e) => e,
+        Err(_) => return Check::Unchecked,
+    };
+    let expected = match serde_json::from_str::<Value>(&expected) {
+        Ok(Value::Object(e)) => e,
+        _ => return Check::Invalid(format!("{} isn't a JSON object", expected.trim())),
+    };
+
+    let differs: Vec<String> = expected.iter()
+        .filter(|(part, answer)| plain(answer) != plain(&answers[part.as_str()]))
+        .map(|(part, _)| part.clone())
+        .collect();
+    if differs.is_empty() { Check::Matches } else { Check::Differs(differs) }
+}
+
+pub fn table(rows: &[Row]) -> String {
+    let parts: Vec<String> = rows.iter()
+        .find_map(|r| match &r.outcome {
+            Outcome::Solved { answers: Value::Object(a), .. } => Some(a.keys().cloned().collect()),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let mut headings = vec![String::from("input")];
+    headings.extend(parts.iter().cloned());
+    headings.extend(vec![String::from("run"), String::from("check")]);
+
+    let mut lines = vec![headings];
+    for row in rows {
+        let mut line = vec![row.name.clone()];
+        match &row.outcome {
+            Outcome::Solved { answers, run, check } => {
+                line.extend(parts.iter().map(|p| plain(&answers[p.as_str()])));
+                line.push(fmt_dur(*run));
+                line.push(match check {
+                    Check::Unchecked => String::from("-"),
+                    Check::Matches => String::from("ok"),
+                    Check::Differs(d) => format!("WRONG {}", d.join(", ")),
+                    Check::Invalid(e) => format!("BAD EXPECTED {}", e),
+                });
+            },
+            Outcome::Panicked(message) => {
+                line.extend(parts.iter().chain(Some(&String::from("run"))).map(|_| String::from("-")));
+                line.push(format!("PANICKED {}", message));
+            },
+        }
+        lines.push(line);
+    }
+    fmt_table(&lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_dir() {
+        let dir = std::env::temp_dir().join(format!("aoc-batch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = "F10\nN3\nF7\nR90\nF11\n";
+        fs::write(dir.join("alice.txt"), input).unwrap();
+        fs::write(dir.join("alice.expected.json"), r#"{ "a": 25, "b": "286" }"#).unwrap();
+        fs::write(dir.join("bob.txt"), input).unwrap();
+        fs::write(dir.join("bob.expected.json"), r#"{ "b": 287 }"#).unwrap();
+        fs::write(dir.join("carol.txt"), "F10\nR45\n").unwrap();
+        fs::write(dir.join("dave.txt"), input).unwrap();
+
+        let rows = run_dir(&registry::find(12).unwrap(), &dir, &Options::default()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(vec!["alice.txt", "bob.txt", "carol.txt", "dave.txt"], rows.iter().map(|r| r.name.as_str()).collect::<Vec<&str>>());
+        assert_eq!(vec![true, false, false, true], rows.iter().map(Row::passed).collect::<Vec<bool>>());
+        assert!(matches!(&rows[1].outcome, Outcome::Solved { check: Check::Differs(d), .. } if d == &vec![String::from("b")]));
+        assert!(matches!(&rows[2].outcome, Outcome::Panicked(m) if m.contains("45 degrees")));
+        assert!(matches!(&rows[3].outcome, Outcome::Solved { check: Check::Unchecked, .. }));
+
+        let table = table(&rows);
+        assert_eq!(vec!["input", "a", "b", "run", "check"], table.lines().next().unwrap().split_whitespace().collect::<Vec<&str>>());
+        assert!(table.contains("WRONG b"));
+        assert!(table.contains("PANICKED"));
+    }
+}
@@ -0,0 +1,391 @@
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+use std::panic;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use common::observer::{self, Event, Observer};
+use common::{fmt_dur, fmt_table, source};
+use crossterm::event::{self as terminal_event, Event as TerminalEvent, KeyCode, KeyEventKind};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use serde_json::Value;
+use crate::registry::{self, plain, Day, Options};
+
+const HELP: &str = "up/down select  enter run  a run all  r progress  e example  i stats  q quit";
+// Rows above the first day in the table: help, blank, headings
+const TABLE_TOP: usize = 3;
+
+pub enum Status {
+    Idle,
+    Queued,
+    Running,
+    Solved { answers: Value, run: Duration },
+    Failed(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Pane {
+    Progress,
+    Example,
+    Stats,
+}
+
+// What's been seen of a day's latest run, from the observer events it emitted
+#[derive(Default)]
+pub struct Progress {
+    pub lines: usize,
+    pub items: usize,
+    pub steps: usize,
+    // The last visual state a simulation emitted, e.g. day 11's seat layout
+    pub state: Option<String>,
+    pub started: Option<Instant>,
+}
+
+pub struct DayRow {
+    pub day: Day,
+    pub status: Status,
+    pub progress: Progress,
+    pub example: Option<String>,
+    pub stats: Option<Vec<(&'static str, String)>>,
+}
+
+pub struct App {
+    pub rows: Vec<DayRow>,
+    pub selected: usize,
+    pub pane: Pane,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Action {
+    Nothing,
+    Run(Vec<usize>),
+    Quit,
+}
+
+// From the worker thread that runs days, one at a time
+pub enum Message {
+    Started(usize),
+    Event(usize, Event),
+    Finished(usize, Result<(Value, Duration), String>),
+}
+
+impl App {
+    pub fn new(days: Vec<Day>) -> App {
+        let rows = days.into_iter()
+            .map(|day| DayRow { day, status: Status::Idle, progress: Progress::default(), example: None, stats: None })
+            .collect();
+        App { rows, selected: 0, pane: Pane::Progress }
+    }
+
+    pub fn key(&mut self, key: KeyCode) -> Action {
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(self.rows.len() - 1),
+            KeyCode::Enter => return self.queue(vec![self.selected]),
+            KeyCode::Char('a') => return self.queue((0..self.rows.len()).collect()),
+            KeyCode::Char('r') => self.pane = Pane::Progress,
+            KeyCode::Char('e') => {
+                self.pane = Pane::Example;
+                let row = &mut self.rows[self.selected];
+                if row.example.is_none() {
+                    row.example = Some(example(&row.day));
+                }
+            },
+            KeyCode::Char('i') => {
+                self.pane = Pane::Stats;
+                let row = &mut self.rows[self.selected];
+                if row.stats.is_none() {
+                    row.stats = Some(match source::read_to_string(&input_path(&row.day)) {
+                        Ok(input) => input_stats(&input),
+                        Err(e) => vec![("error", e.to_string())],
+                    });
+                }
+            },
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            _ => {},
+        }
+        Action::Nothing
+    }
+
+    // Days already queued or running aren't queued again
+    fn queue(&mut self, indices: Vec<usize>) -> Action {
+        let indices: Vec<usize> = indices.into_iter()
+            .filter(|i| !matches!(self.rows[*i].status, Status::Queued | Status::Running))
+            .collect();
+        for i in &indices {
+            self.rows[*i].status = Status::Queued;
+        }
+        Action::Run(indices)
+    }
+
+    pub fn message(&mut self, message: Message) {
+        match message {
+            Message::Started(i) => {
+                self.rows[i].status = Status::Running;
+                self.rows[i].progress = Progress { started: Some(Instant::now()), ..Progress::default() };
+            },
+            Message::Event(i, event) => {
+                let progress = &mut self.rows[i].progress;
+                match event {
+                    Event::LineParsed { .. } => progress.lines += 1,
+                    Event::ItemProcessed { .. } => progress.items += 1,
+                    Event::SimulationStep { step, state } => {
+                        progress.steps = step;
+                        if state.is_some() {
+                            progress.state = state;
+                        }
+                    },
+                    Event::Counter { .. } => {},
+                }
+            },
+            Message::Finished(i, Ok((answers, run))) => self.rows[i].status = Status::Solved { answers, run },
+            Message::Finished(i, Err(e)) => self.rows[i].status = Status::Failed(e),
+        }
+    }
+}
+
+fn input_path(day: &Day) -> PathBuf {
+    registry::dir(day.day).join("input.txt")
+}
+
+fn example(day: &Day) -> String {
+    let answers = match registry::solve(day, day.example, &day.example_options) {
+        Ok(solved) => answer_text(&solved.answers),
+        Err(e) => format!("panicked: {}", e),
+    };
+    format!("{}\nAnswers: {}", day.example.trim_end(), answers)
+}
+
+fn answer_text(answers: &Value) -> String {
+    answers.as_object()
+        .map(|a| a.iter().map(|(part, answer)| format!("{}: {}", part, plain(answer))).collect::<Vec<String>>().join(", "))
+        .unwrap_or_default()
+}
+
+// Size and shape of an input, to see at a glance whether it's the one expected
+pub fn input_stats(input: &str) -> Vec<(&'static str, String)> {
+    let lines: Vec<&str> = input.lines().collect();
+    let blank = lines.iter().filter(|l| l.trim().is_empty()).count();
+    let records = input.split("\n\n").filter(|r| !r.trim().is_empty()).count();
+    let widths: Vec<usize> = lines.iter().filter(|l| !l.trim().is_empty()).map(|l| l.chars().count()).collect();
+    let characters: BTreeSet<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+
+    vec![
+        ("bytes", input.len().to_string()),
+        ("lines", lines.len().to_string()),
+        ("blank lines", blank.to_string()),
+        ("records", records.to_string()),
+        ("line width", format!("{} to {}", widths.iter().min().unwrap_or(&0), widths.iter().max().unwrap_or(&0))),
+        ("characters", if characters.len() <= 32 { characters.iter().collect() } else { format!("{} distinct", characters.len()) }),
+    ]
+}
+
+// The whole screen as lines of text, at most `width` characters wide and `height` tall
+pub fn render(app: &App, width: usize, height: usize) -> Vec<String> {
+    let mut lines = vec![String::from(HELP), String::new()];
+
+    let mut table = vec![["day", "title", "status", "a", "b", "run"].iter().map(|h| h.to_string()).collect::<Vec<String>>()];
+    for row in &app.rows {
+        let (status, a, b, run) = match &row.status {
+            Status::Idle => (String::from("-"), String::new(), String::new(), String::new()),
+            Status::Queued => (String::from("queued"), String::new(), String::new(), String::new()),
+            Status::Running => (String::from("running"), String::new(), String::new(),
+                                row.progress.started.map(|s| fmt_dur(s.elapsed())).unwrap_or_default()),
+            Status::Solved { answers, run } => (String::from("ok"), plain(&answers["a"]), plain(&answers["b"]), fmt_dur(*run)),
+            Status::Failed(_) => (String::from("FAILED"), String::new(), String::new(), String::new()),
+        };
+        table.push(vec![row.day.day.to_string(), row.day.title.to_string(), status, a, b, run]);
+    }
+    lines.extend(fmt_table(&table).lines().map(String::from));
+    lines.push(String::new());
+
+    let row = &app.rows[app.selected];
+    match app.pane {
+        Pane::Progress => {
+            lines.push(format!("Day {}: {}", row.day.day, row.day.title));
+            let p = &row.progress;
+            match &row.status {
+                Status::Idle | Status::Queued => lines.push(String::from("Not run yet")),
+                Status::Failed(e) => lines.push(format!("Failed: {}", e)),
+                _ => lines.push(format!("{} lines parsed, {} items processed, {} simulation steps", p.lines, p.items, p.steps)),
+            }
+            if let Some(state) = &p.state {
+                lines.push(String::new());
+                lines.extend(state.lines().map(String::from));
+            }
+        },
+        Pane::Example => {
+            lines.push(format!("Day {} example", row.day.day));
+            lines.extend(row.example.as_deref().unwrap_or("").lines().map(String::from));
+        },
+        Pane::Stats => {
+            lines.push(format!("Day {} input", row.day.day));
+            let stats: Vec<Vec<String>> = row.stats.iter().flatten().map(|(k, v)| vec![k.to_string(), v.clone()]).collect();
+            lines.extend(fmt_table(&stats).lines().map(String::from));
+        },
+    }
+
+    lines.truncate(height);
+    lines.iter().map(|l| l.chars().take(width).collect()).collect()
+}
+
+struct Forward {
+    index: usize,
+    sender: Sender<Message>,
+}
+
+impl Observer for Forward {
+    fn event(&mut self, event: &Event) {
+        let copy = match event {
+            Event::LineParsed { line } => Event::LineParsed { line: *line },
+            Event::ItemProcessed { index } => Event::ItemProcessed { index: *index },
+            Event::SimulationStep { step, state } => Event::SimulationStep { step: *step, state: state.clone() },
+            Event::Counter { name, value } => Event::Counter { name, value: *value },
+        };
+        let _ = self.sender.send(Message::Event(self.index, copy));
+    }
+}
+
+// Runs queued days one after another, since there's only one observer to watch them with
+fn worker(days: Vec<Day>, options: Options, requests: Receiver<usize>, sender: Sender<Message>) {
+    for index in requests {
+        let day = &days[index];
+        let _ = sender.send(Message::Started(index));
+        let result = source::read_to_string(&input_path(day)).map_err(|e| e.to_string()).and_then(|input| {
+            observer::set_observer(Some(Box::new(Forward { index, sender: sender.clone() })));
+            let solved = registry::solve(day, &input, &options);
+            observer::set_observer(None);
+            solved.map(|s| (s.answers, s.reports.iter().map(|r| r.run).sum()))
+        });
+        let _ = sender.send(Message::Finished(index, result));
+    }
+}
+
+// Puts the terminal back however the dashboard exits
+struct Screen;
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), terminal::LeaveAlternateScreen, cursor::Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// `aoc dashboard [--preamble N]`
+pub fn run() {
+    let mut app = App::new(registry::days());
+    let (request_sender, requests) = mpsc::channel();
+    let (sender, messages) = mpsc::channel();
+    let days = registry::days();
+    let options = Options::from_args();
+    thread::spawn(move || worker(days, options, requests, sender));
+
+    // A panicking day is shown as failed, the default hook would only scribble over the screen
+    panic::set_hook(Box::new(|_| {}));
+    terminal::enable_raw_mode().expect("Failed to set up terminal");
+    let _screen = Screen;
+    execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide).expect("Failed to set up terminal");
+
+    loop {
+        while let Ok(message) = messages.try_recv() {
+            app.message(message);
+        }
+        draw(&app).expect("Failed to draw");
+
+        if terminal_event::poll(Duration::from_millis(100)).unwrap_or(false) {
+            if let Ok(TerminalEvent::Key(key)) = terminal_event::read() {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match app.key(key.code) {
+                    Action::Quit => break,
+                    Action::Run(indices) => indices.into_iter().for_each(|i| request_sender.send(i).expect("Worker stopped")),
+                    Action::Nothing => {},
+                }
+            }
+        }
+    }
+    let _ = panic::take_hook();
+}
+
+fn draw(app: &App) -> io::Result<()> {
+    let (width, height) = terminal::size()?;
+    let mut out = io::stdout();
+    for (y, line) in render(app, width as usize, height as usize).iter().enumerate() {
+        queue!(out, cursor::MoveTo(0, y as u16), terminal::Clear(ClearType::CurrentLine))?;
+        if y == TABLE_TOP + app.selected {
+            queue!(out, SetAttribute(Attribute::Reverse), Print(line), SetAttribute(Attribute::Reset))?;
+        } else {
+            queue!(out, Print(line))?;
+        }
+    }
+    queue!(out, terminal::Clear(ClearType::FromCursorDown))?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_and_messages() {
+        let mut app = App::new(registry::days());
+        assert_eq!(Action::Nothing, app.key(KeyCode::Up));
+        assert_eq!(0, app.selected);
+        app.key(KeyCode::Down);
+        app.key(KeyCode::Char('j'));
+        assert_eq!(2, app.selected);
+
+        assert_eq!(Action::Run(vec![2]), app.key(KeyCode::Enter));
+        assert_eq!(Action::Run(vec![0, 1, 3, 4, 5, 6, 7, 8, 9, 10, 11]), app.key(KeyCode::Char('a')));
+
+        app.message(Message::Started(10));
+        app.message(Message::Event(10, Event::LineParsed { line: 1 }));
+        app.message(Message::Event(10, Event::SimulationStep { step: 1, state: Some(String::from("#.#\n")) }));
+        app.message(Message::Event(10, Event::SimulationStep { step: 2, state: None }));
+        let progress = &app.rows[10].progress;
+        assert_eq!((1, 2, Some("#.#\n")), (progress.lines, progress.steps, progress.state.as_deref()));
+
+        app.message(Message::Finished(10, Ok((serde_json::json!({ "a": 37, "b": 26 }), Duration::from_millis(3)))));
+        assert!(matches!(app.rows[10].status, Status::Solved { .. }));
+        assert_eq!(Action::Quit, app.key(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn test_render() {
+        let mut app = App::new(registry::days());
+        app.selected = 10;
+        app.message(Message::Started(10));
+        app.message(Message::Event(10, Event::SimulationStep { step: 4, state: Some(String::from("#.##\nL.L#\n")) }));
+        app.message(Message::Finished(10, Ok((serde_json::json!({ "a": 37, "b": 26 }), Duration::from_millis(3)))));
+        app.message(Message::Finished(11, Err(String::from("Can't move with 45 degrees!"))));
+
+        let screen = render(&app, 80, 40);
+        assert_eq!(HELP, screen[0]);
+        assert!(screen[TABLE_TOP + 10].starts_with("11   Seating System"));
+        assert!(screen[TABLE_TOP + 10].contains("ok      37  26  3ms"));
+        assert!(screen[TABLE_TOP + 11].contains("FAILED"));
+        assert!(screen.contains(&String::from("0 lines parsed, 0 items processed, 4 simulation steps")));
+        assert!(screen.ends_with(&[String::from("#.##"), String::from("L.L#")]));
+
+        app.key(KeyCode::Char('e'));
+        let screen = render(&app, 80, 40);
+        assert_eq!("Answers: a: 37, b: 26", screen.last().unwrap());
+        assert!(render(&app, 10, 5).iter().all(|l| l.chars().count() <= 10));
+        assert_eq!(5, render(&app, 10, 5).len());
+    }
+
+    #[test]
+    fn test_input_stats() {
+        let stats = input_stats("ecl:gry pid:1\nhcl:#fff\n\nbyr:1937\n");
+        assert_eq!(("bytes", String::from("33")), stats[0]);
+        assert_eq!(("lines", String::from("4")), stats[1]);
+        assert_eq!(("blank lines", String::from("1")), stats[2]);
+        assert_eq!(("records", String::from("2")), stats[3]);
+        assert_eq!(("line width", String::from("8 to 13")), stats[4]);
+        assert_eq!(("characters", String::from("#1379:bcdefghilpry")), stats[5]);
+    }
+}
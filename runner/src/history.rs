@@ -0,0 +1,105 @@
+use common::history::{self, Entry};
+use common::{args, fmt_dur, fmt_table};
+use crate::registry;
+
+pub const DEFAULT_LAST: usize = 20;
+
+// `aoc history --day N [--last K] [--regression-margin PCT]`
+pub fn run() {
+    let day: u32 = args::value("--day").expect("History needs --day").parse().expect("Bad --day");
+    let path = registry::dir(day).join(history::FILE);
+    let entries = history::load(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+    if entries.is_empty() {
+        println!("No history for day {} yet, `./aoc run --day {}` records some", day, day);
+        return;
+    }
+
+    let last = args::value("--last").map_or(DEFAULT_LAST, |l| l.parse().expect("Bad --last"));
+    print!("{}", report(&entries, last, history::margin()));
+}
+
+// Per part: a summary, then its last `last` runs with how each compared to the rolling
+// median of the runs before it on the same input
+pub fn report(entries: &[Entry], last: usize, margin: f64) -> String {
+    let mut parts: Vec<usize> = entries.iter().map(|e| e.part).collect();
+    parts.sort_unstable();
+    parts.dedup();
+
+    let mut out = String::new();
+    for part in parts {
+        let runs: Vec<(usize, &Entry)> = entries.iter().enumerate().filter(|(_, e)| e.part == part).collect();
+        let mut durations: Vec<_> = runs.iter().map(|(_, e)| e.run).collect();
+        let best = *durations.iter().min().unwrap();
+        out.push_str(&format!("Part {} ({}): {} runs, median {}, best {}\n", part, runs[0].1.puzzle, runs.len(),
+                              fmt_dur(history::median(&mut durations)), fmt_dur(best)));
+
+        let mut rows = vec![["when", "commit", "input", "run", "trend", "result"].iter().map(|h| h.to_string()).collect()];
+        for (index, entry) in runs.iter().skip(runs.len().saturating_sub(last)) {
+            let trend = match history::compare(&entries[..*index], entry) {
+                Some(c) if c.percent > margin => format!("{:+.0}% REGRESSION", c.percent),
+                Some(c) => format!("{:+.0}%", c.percent),
+                None => String::from("-"),
+            };
+            rows.push(vec![date(entry.time), entry.commit.clone(), entry.input_hash.chars().take(8).collect(),
+                           fmt_dur(entry.run), trend, entry.result.clone()]);
+        }
+        out.push_str(&fmt_table(&rows));
+        out.push('\n');
+    }
+    out
+}
+
+// UTC, from the days since 1970 to a civil date as in Howard Hinnant's `civil_from_days`
+fn date(unix: u64) -> String {
+    let days = (unix / 86_400) as i64;
+    let (hour, minute) = (unix % 86_400 / 3600, unix % 3600 / 60);
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, minute)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_date() {
+        assert_eq!("1970-01-01 00:00", date(0));
+        assert_eq!("2020-12-01 05:00", date(1_606_798_800));
+        assert_eq!("2024-02-29 23:59", date(1_709_251_140));
+    }
+
+    #[test]
+    fn test_report() {
+        let run = |part: usize, commit: &str, run_ms: u64| Entry {
+            time: 1_606_798_800,
+            day: String::from("day_12"),
+            part,
+            puzzle: format!("Puzzle{}", part),
+            commit: String::from(commit),
+            input_hash: String::from("cbf29ce484222325"),
+            run: Duration::from_millis(run_ms),
+            result: String::from("25"),
+            options: String::new(),
+        };
+        let entries = vec![run(1, "aaaaaaa", 10), run(2, "aaaaaaa", 50), run(1, "aaaaaaa", 10), run(1, "aaaaaaa", 12),
+                           run(1, "bbbbbbb", 11), run(1, "ccccccc", 30)];
+
+        let report = report(&entries, 2, history::DEFAULT_MARGIN);
+        assert!(report.starts_with("Part 1 (Puzzle1): 5 runs, median 11ms 0µs, best 10ms 0µs\n"));
+        assert!(report.contains("Part 2 (Puzzle2): 1 runs"));
+        assert!(!report.contains("aaaaaaa  cbf29ce4  10ms 0µs"));
+        assert!(report.contains("bbbbbbb  cbf29ce4  11ms 0µs  +10%"));
+        assert!(report.contains("ccccccc  cbf29ce4  30ms 0µs  +186% REGRESSION"));
+    }
+}
@@ -0,0 +1,103 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use common::args;
+use common::vault::{self, Key};
+use crate::registry::{self, root};
+
+const INPUT: &str = "input.txt";
+
+// `aoc input keygen|encrypt|decrypt [--day N] [--keep]`, over each day's `input.txt`.
+// `--keep` keeps the plain input when encrypting. Decrypting always keeps the ciphertext,
+// which is what's committed.
+pub fn run() {
+    let action = std::env::args().nth(2).unwrap_or_default();
+    if action == "keygen" {
+        return keygen();
+    }
+    if action != "encrypt" && action != "decrypt" {
+        eprintln!("Usage: aoc input keygen|encrypt|decrypt [--day N] [--keep]");
+        std::process::exit(2);
+    }
+
+    let key = Key::find().unwrap_or_else(|e| panic!("{}", e));
+    let keep = args::flag("--keep");
+    for dir in day_dirs() {
+        let plain = dir.join(INPUT);
+        let written = match action.as_str() {
+            "encrypt" if plain.exists() => encrypt(&key, &plain, keep),
+            "decrypt" if vault::encrypted_path(&plain).exists() => decrypt(&key, &plain),
+            _ => continue,
+        };
+        match written {
+            Ok(path) => println!("{}", path.display()),
+            Err(e) => panic!("Failed to {} {}: {}", action, dir.display(), e),
+        }
+    }
+}
+
+// Writes a new key to `.aoc-key` in the repository, never over an existing one
+fn keygen() {
+    let path = root().join(vault::KEY_FILE);
+    if path.exists() {
+        panic!("{} already exists", path.display());
+    }
+    fs::write(&path, Key::generate().to_hex() + "\n").expect("Failed to write key");
+    println!("{}", path.display());
+}
+
+// Unless kept, the plain input is removed so only the ciphertext gets committed
+fn encrypt(key: &Key, plain: &Path, keep: bool) -> io::Result<PathBuf> {
+    let encrypted = vault::encrypted_path(plain);
+    fs::write(&encrypted, vault::encrypt(key, &fs::read(plain)?))?;
+    if !keep {
+        fs::remove_file(plain)?;
+    }
+    Ok(encrypted)
+}
+
+fn decrypt(key: &Key, plain: &Path) -> io::Result<PathBuf> {
+    let encrypted = vault::encrypted_path(plain);
+    fs::write(plain, vault::decrypt(key, &fs::read(&encrypted)?)?)?;
+    Ok(plain.to_path_buf())
+}
+
+fn day_dirs() -> Vec<PathBuf> {
+    match args::value("--day") {
+        Some(day) => vec![registry::dir(day.parse().expect("Bad --day"))],
+        None => {
+            let mut dirs: Vec<PathBuf> = fs::read_dir(root()).expect("Failed to list days")
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with("day-")))
+                .collect();
+            dirs.sort();
+            dirs
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let dir = std::env::temp_dir().join(format!("aoc-input-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let plain = dir.join(INPUT);
+        fs::write(&plain, "F10\nN3\n").unwrap();
+        let key = Key::generate();
+
+        let encrypted = encrypt(&key, &plain, false).unwrap();
+        let plain_removed = !plain.exists();
+        let decrypted = decrypt(&key, &plain).unwrap();
+        let contents = fs::read_to_string(&decrypted).unwrap();
+        let both_kept = encrypted.exists() && plain.exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(dir.join("input.txt.enc"), encrypted);
+        assert!(plain_removed);
+        assert!(both_kept);
+        assert_eq!("F10\nN3\n", contents);
+    }
+}
@@ -0,0 +1,26 @@
+use common::args;
+
+mod batch;
+mod dashboard;
+mod history;
+mod input;
+mod registry;
+mod serve;
+
+fn main() {
+    match args::command().as_deref() {
+        Some("serve") => serve::run(),
+        Some("run") if args::flag("--inputs") => batch::run(),
+        Some("input") => input::run(),
+        Some("history") => history::run(),
+        Some("dashboard") => dashboard::run(),
+        _ => {
+            eprintln!("Usage: aoc serve [--port N] [--max-body BYTES] [--timeout-ms MS] [--io-timeout-ms MS] [--max-solves N]");
+            eprintln!("       aoc run --day N --inputs DIR");
+            eprintln!("       aoc input keygen|encrypt|decrypt [--day N] [--keep]");
+            eprintln!("       aoc history --day N [--last K] [--regression-margin PCT]");
+            eprintln!("       aoc dashboard");
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use common::{args, ParallelPuzzle, Puzzle, RunReport};
+use serde_json::{json, Value};
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Every day the runner knows about. `run` runs each part the way the day's own binary does,
+// and the answers are read off its reports. `example` is the puzzle text's example input, run
+// with `example_options`.
+#[derive(Clone, Copy)]
+pub struct Day {
+    pub day: u32,
+    pub title: &'static str,
+    pub example: &'static str,
+    pub run: fn(&str, &Options) -> Vec<RunReport>,
+    pub example_options: Options,
+    pub timeout: Duration,
+}
+
+// Settings a day can't tell from its input, given alongside it. Day 9's preamble is the only
+// one so far.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Options {
+    pub preamble: usize,
+}
+
+impl Options {
+    // `--preamble N`
+    pub fn from_args() -> Options {
+        Options { preamble: args::value("--preamble").map_or(day_09::PREAMBLE_LENGTH, |p| p.parse().expect("Bad --preamble")) }
+    }
+
+    // `preamble=5`, e.g. from a query string. Anything not given keeps its default.
+    pub fn from_query(query: &str) -> Result<Options, String> {
+        let mut options = Options::default();
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            match pair.split_once('=') {
+                Some(("preamble", n)) => options.preamble = n.parse().map_err(|_| format!("Bad preamble {:?}", n))?,
+                _ => return Err(format!("Unknown option {:?}", pair)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options { preamble: day_09::PREAMBLE_LENGTH }
+    }
+}
+
+pub fn days() -> Vec<Day> {
+    vec![
+        Day {
+            day: 1,
+            title: "Report Repair",
+            example: "1721\n979\n366\n299\n675\n1456\n",
+            // Exact products, so no answer is lost to an overflow
+            run: |input, _| vec![
+                day_01::ExpenseReport::pair().precision(day_01::Precision::Big).run_with_input(input.to_string()),
+                day_01::ExpenseReport::triple().precision(day_01::Precision::Big).run_with_input(input.to_string()),
+            ],
+            example_options: Options::default(),
+            timeout: DEFAULT_TIMEOUT,
+        },
+        Day {
+            day: 2,
+            title: "Password Philosophy",
+            example: "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n",
+            run: |input, _| vec![
+                day_02::Passwords::part_a().run_parallel_with_input(input.to_string()),
+                day_02::Passwords::part_b().run_parallel_with_input(input.to_string()),
+            ],
+            example_options: Options::default(),
+            timeout: DEFAULT_TIMEOUT,
+        },
+        Day {
+            day: 3,
+            title: "Toboggan Trajectory",
+            example: "..##.......\n#...#...#..\n.#....#..#.\n..#.#...#.#\n.#...##..#.\n..#.##.....\n.#.#.#....#\n.#........#\n#.##...#...\n#...##....#\n.#..#...#.#\n",
+            run: |input, _| vec![day_03::Puzzle1::default().run_with_input(input.to_string())],
+            example_options: Options::default(),
+            timeout: DEFAULT_TIMEOUT,
+        },
+        Day {
+            day: 4,
+            title: "Passport Processing",
+            example: "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm\n\niyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884\nhcl:#cfa07d byr:1929\n\nhcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm\n\nhcl:#cfa07d eyr:2025 pid:166559648\niyr:2011 ecl:brn hgt:59in\n",
+            run: |input, _| vec![
+                day_04::Puzzle1::new(day_04::RuleSet::Casual).run_parallel_with_input(input.to_string()),
+                day_04::Puzzle1::new(day_04::RuleSet::Strict).run_parallel_with_input(input.to_string()),
+            ],
+            example_options: Options::default(),
+            timeout: DEFAULT_TIMEOUT,
+        },
+        Day {
+            day: 5,
+            title: "Binary Boarding",
+            example: "FBFBBFFRLR\nBFFFBBFRRR\nFFFBBBFRRR\nBBFFBBFRLL\n",
+            run: |input, _| vec![day_05::Puzzle1::default().run_parallel_with_input(input.to_string())],
+            example_options: Options::default(),
+            timeout: DEFAULT_TIMEOUT,
+        },
+        Day {
+            day: 6,
+            title: "Custom Customs",
+            example: "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb\n",
+            run: |input, _| vec![day_06::Puzzle1::default().run_with_input(input.to_string())],
+            example_options: Options::default(),
+            timeout: DEFAULT_TIMEOUT,
+        },
+        Day {
+            day: 7,
+            title: "Handy Haversacks",
+            example: "light red bags contain 1 bright white bag, 2 muted yellow bags.\ndark orange bags contain 3 bright white bags, 4 muted yellow bags.\nbright white bags contain 1 shiny gold bag.\nmuted yellow bags contain 2 shiny gold bags, 9 faded blue bags.\nshiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.\ndark olive bags contain 3 faded blue bags, 4 dotted black bags.\nvibrant plum bags contain 5 faded blue bags, 6 dotted black bags.\nfaded blue bags contain no other bags.\ndotted black bags contain no other bags.\n",
+            run: |input, _| vec![day_07::Puzzle1::new().run_with_input(input.to_string())],
+            example_options: Options::default(),
+            timeout: DEFAULT_TIMEOUT,
+        },
+        Day {
+            day: 8,
+            title: "Handheld Halting",
+            example: "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6\n",
+            run: |input, _| vec![day_08::Puzzle1::default().run_with_input(input.to_string())],
+            example_options: Options::default(),
+            timeout: DEFAULT_TIMEOUT,
+        },
+        Day {
+            day: 9,
+            title: "Encoding Error",
+            example: "35\n20\n15\n25\n47\n40\n62\n55\n65\n95\n102\n117\n150\n182\n127\n219\n299\n277\n309\n576\n",
+            run: |input, options| vec![day_09::Puzzle1::new(options.preamble).run_with_input(input.to_string())],
+            example_options: Options { preamble: 5 },
+            timeout: DEFAULT_TIMEOUT,
+        },
+        Day {
+            day: 10,
+            title: "Adapter Array",
+            example: "16\n10\n15\n5\n1\n11\n7\n19\n6\n12\n4\n",
+            run: |input, _| vec![day_10::Puzzle1::default().run_with_input(input.to_string())],
+            example_options: Options::default(),
+            timeout: DEFAULT_TIMEOUT,
+        },
+        Day {
+            day: 11,
+            title: "Seating System",
+            example: "L.LL.LL.LL\nLLLLLLL.LL\nL.L.L..L..\nLLLL.LL.LL\nL.LL.LL.LL\nL.LLLLL.LL\n..L.L.....\nLLLLLLLLLL\nL.LLLLLL.L\nL.LLLLL.LL\n",
+            run: |input, _| vec![day_11::Puzzle1::default().run_with_input(input.to_string())],
+            example_options: Options::default(),
+            timeout: Duration::from_secs(30),
+        },
+        Day {
+            day: 12,
+            title: "Rain Risk",
+            example: "F10\nN3\nF7\nR90\nF11\n",
+            run: |input, _| vec![
+                day_12::Puzzle1::new().run_with_input(input.to_string()),
+                day_12::Puzzle2::new().run_with_input(input.to_string()),
+            ],
+            example_options: Options::default(),
+            timeout: DEFAULT_TIMEOUT,
+        },
+    ]
+}
+
+pub fn find(day: u32) -> Option<Day> {
+    days().into_iter().find(|d| d.day == day)
+}
+
+pub fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+// Where the day's crate, input and history live
+pub fn dir(day: u32) -> PathBuf {
+    root().join(format!("day-{:02}", day))
+}
+
+pub struct Solved {
+    pub answers: Value,
+    pub reports: Vec<RunReport>,
+}
+
+// The reported runs and the answers in them, with a panic turned into its message. Each
+// input is only solved once.
+pub fn solve(day: &Day, input: &str, options: &Options) -> Result<Solved, String> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let reports = (day.run)(input, options);
+        Solved { answers: answers(&reports), reports }
+    }))
+        .map_err(|panic| panic.downcast_ref::<String>().cloned()
+            .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| String::from("unknown panic")))
+}
+
+// Each part's answer from the reported results, which are either one result per part or one
+// result with the parts separated by `,` or `;`. An answer is the last word of its part, or
+// null when that isn't a number, as in "Not found".
+fn answers(reports: &[RunReport]) -> Value {
+    let parts: Vec<Value> = reports.iter()
+        .flat_map(|r| r.result.split([',', ';']))
+        .map(|part| part.split_whitespace().last().filter(|w| is_number(w)).map_or(Value::Null, big))
+        .collect();
+    json!({ "a": parts.first(), "b": parts.get(1) })
+}
+
+fn is_number(word: &str) -> bool {
+    let digits = word.strip_prefix('-').unwrap_or(word);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+// An answer as text, whether it's a number or a string. This also lets an expected answer
+// be written either way.
+pub fn plain(answer: &Value) -> String {
+    match answer {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+// JSON numbers stop at i64 and u64, so anything beyond goes out as a string
+fn big<N: ToString>(n: N) -> Value {
+    let n = n.to_string();
+    n.parse::<u64>().map(Value::from)
+        .or_else(|_| n.parse::<i64>().map(Value::from))
+        .unwrap_or(Value::from(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let days = days();
+        assert_eq!((1..=12).collect::<Vec<u32>>(), days.iter().map(|d| d.day).collect::<Vec<u32>>());
+        assert!(find(13).is_none());
+
+        let day_12 = find(12).unwrap();
+        let input = "F10\nN3\nF7\nR90\nF11\n";
+        let reports = (day_12.run)(input, &Options::default());
+        assert_eq!(vec!["25", "286"], reports.iter().map(|r| r.result.as_str()).collect::<Vec<&str>>());
+
+        assert_eq!(json!({ "a": 25, "b": 286 }), solve(&day_12, input, &Options::default()).ok().unwrap().answers);
+        assert!(solve(&day_12, "F10\nR45\n", &Options::default()).err().unwrap().contains("45 degrees"));
+    }
+
+    #[test]
+    fn test_examples() {
+        for day in days() {
+            let solved = solve(&day, day.example, &day.example_options).unwrap_or_else(|e| panic!("Day {} example panicked: {}", day.day, e));
+            assert!(solved.answers["a"].is_number(), "Day {} example has no answer", day.day);
+            assert!(solved.answers["b"].is_number(), "Day {} example has no second answer", day.day);
+        }
+        let example = |day| {
+            let day = find(day).unwrap();
+            solve(&day, day.example, &day.example_options).unwrap().answers
+        };
+        assert_eq!(json!({ "a": 820, "b": 120 }), example(5));
+        assert_eq!(json!({ "a": 4, "b": 32 }), example(7));
+        assert_eq!(json!({ "a": 127, "b": 62 }), example(9));
+        assert_eq!(json!({ "a": 514579, "b": 241861950 }), example(1));
+    }
+
+    #[test]
+    fn test_options() {
+        assert_eq!(Options { preamble: 25 }, Options::default());
+        assert_eq!(Ok(Options { preamble: 5 }), Options::from_query("preamble=5"));
+        assert_eq!(Ok(Options::default()), Options::from_query(""));
+        assert!(Options::from_query("preamble=x").is_err());
+        assert!(Options::from_query("colour=red").is_err());
+
+        // The example has no answer with the default preamble, rather than a guessed one
+        let day_9 = find(9).unwrap();
+        assert_eq!(Value::Null, solve(&day_9, day_9.example, &Options::default()).map_or(Value::Null, |s| s.answers["a"].clone()));
+    }
+
+    #[test]
+    fn test_big() {
+        assert_eq!(json!(u64::MAX), big(u64::MAX as u128));
+        assert_eq!(json!(-3), big(-3i128));
+        assert_eq!(json!("340282366920938463463374607431768211455"), big(u128::MAX));
+        assert_eq!(json!({ "a": "-35999987880000000000", "b": null }), solve(&find(1).unwrap(), "6000000000\n-5999997980\n", &Options::default()).unwrap().answers);
+        let report = RunReport { result: String::from("a: 3; b: too many to count"), run: Duration::default(), phases: Vec::new(), threads: 1 };
+        assert_eq!(json!({ "a": 3, "b": null }), answers(&[report]));
+    }
+}
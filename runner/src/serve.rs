@@ -0,0 +1,369 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use common::args;
+use serde_json::{json, Value};
+use crate::registry::{self, Day, Options};
+
+pub const DEFAULT_PORT: u16 = 2020;
+pub const DEFAULT_MAX_BODY: usize = 1 << 20;
+pub const DEFAULT_IO_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_MAX_SOLVES: usize = 4;
+// Longest request or header line, and most header bytes in all
+pub const MAX_LINE: usize = 8 << 10;
+pub const MAX_HEADERS: usize = 32 << 10;
+
+#[derive(Clone, Copy)]
+pub struct Limits {
+    pub max_body: usize,
+    // Overrides every day's own timeout when set
+    pub timeout: Option<Duration>,
+    // How long a client may take to send its request or read the response
+    pub io_timeout: Duration,
+    // Puzzles running at once, counting ones whose requests have timed out
+    pub max_solves: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits { max_body: DEFAULT_MAX_BODY, timeout: None, io_timeout: DEFAULT_IO_TIMEOUT, max_solves: DEFAULT_MAX_SOLVES }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Response {
+        Response { status, body: json!({ "error": message }) }
+    }
+}
+
+// `aoc serve [--port N] [--max-body BYTES] [--timeout-ms MS] [--io-timeout-ms MS] [--max-solves N]`,
+// localhost only
+pub fn run() {
+    let port = args::value("--port").map_or(DEFAULT_PORT, |p| p.parse().expect("Bad --port"));
+    let limits = Limits {
+        max_body: args::value("--max-body").map_or(DEFAULT_MAX_BODY, |b| b.parse().expect("Bad --max-body")),
+        timeout: args::value("--timeout-ms").map(|t| Duration::from_millis(t.parse().expect("Bad --timeout-ms"))),
+        io_timeout: args::value("--io-timeout-ms").map_or(DEFAULT_IO_TIMEOUT, |t| Duration::from_millis(t.parse().expect("Bad --io-timeout-ms"))),
+        max_solves: args::value("--max-solves").map_or(DEFAULT_MAX_SOLVES, |n| n.parse().expect("Bad --max-solves")),
+    };
+
+    let listener = TcpListener::bind(("127.0.0.1", port)).expect("Failed to bind");
+    println!("Serving on http://{}", listener.local_addr().unwrap());
+    serve(listener, limits);
+}
+
+// Counts the puzzles running. A slot is held by the puzzle's own thread, so one that runs past
+// its timeout still counts until it finishes.
+#[derive(Clone, Default)]
+struct Running(Arc<AtomicUsize>);
+
+struct Slot(Arc<AtomicUsize>);
+
+impl Running {
+    fn claim(&self, max: usize) -> Option<Slot> {
+        self.0.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| if n < max { Some(n + 1) } else { None })
+            .ok()
+            .map(|_| Slot(self.0.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub fn serve(listener: TcpListener, limits: Limits) {
+    let running = Running::default();
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let running = running.clone();
+                thread::spawn(move || handle(stream, limits, &running));
+            },
+            Err(e) => eprintln!("Connection failed: {}", e),
+        }
+    }
+}
+
+fn handle(stream: TcpStream, limits: Limits, running: &Running) {
+    // A slow client gives up its thread once either times out
+    let _ = stream.set_read_timeout(Some(limits.io_timeout));
+    let _ = stream.set_write_timeout(Some(limits.io_timeout));
+    let mut reader = BufReader::new(&stream);
+    let response = match read_request(&mut reader, limits.max_body) {
+        Ok(request) => route(&request, limits, running),
+        Err(response) => response,
+    };
+
+    let body = response.body.to_string();
+    let mut out = &stream;
+    let _ = write!(out, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                   response.status, reason(response.status), body.len(), body);
+}
+
+// A client that stops sending gets a 408, anything else unreadable a 400
+fn read_error(e: io::Error) -> Response {
+    match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Response::error(408, "Timed out reading the request"),
+        _ => Response::error(400, "Malformed request"),
+    }
+}
+
+// One line of at most `max` bytes, or None if it runs longer. No more than `max` bytes are
+// read, however long the line really is.
+fn read_line<R: BufRead>(reader: &mut R, max: usize) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    reader.by_ref().take(max as u64).read_line(&mut line)?;
+    if line.len() == max && !line.ends_with('\n') {
+        Ok(None)
+    } else {
+        Ok(Some(line))
+    }
+}
+
+fn read_request<R: BufRead>(reader: &mut R, max_body: usize) -> Result<Request, Response> {
+    let bad_request = || Response::error(400, "Malformed request");
+
+    let request_line = read_line(reader, MAX_LINE).map_err(read_error)?
+        .ok_or_else(|| Response::error(400, &format!("Request line is over {} bytes", MAX_LINE)))?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().ok_or_else(bad_request)?.to_string();
+    let path = parts.next().ok_or_else(bad_request)?.to_string();
+
+    let mut content_length = 0;
+    let mut header_bytes = 0;
+    loop {
+        let header = read_line(reader, MAX_LINE).map_err(read_error)?
+            .ok_or_else(|| Response::error(431, &format!("A header line is over {} bytes", MAX_LINE)))?;
+        header_bytes += header.len();
+        if header_bytes > MAX_HEADERS {
+            return Err(Response::error(431, &format!("Headers are over {} bytes", MAX_HEADERS)));
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| bad_request())?;
+            }
+        }
+    }
+
+    // Refuse before reading, so an oversized body never ends up in memory
+    if content_length > max_body {
+        return Err(Response::error(413, &format!("Body is {} bytes, the limit is {}", content_length, max_body)));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(read_error)?;
+
+    Ok(Request { method, path, body })
+}
+
+fn route(request: &Request, limits: Limits, running: &Running) -> Response {
+    let (path, query) = request.path.split_once('?').unwrap_or((&request.path, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["puzzles"]) => {
+            let puzzles: Vec<Value> = registry::days().iter()
+                .map(|d| json!({ "day": d.day, "title": d.title, "timeout_ms": d.timeout.as_millis() as u64 }))
+                .collect();
+            Response { status: 200, body: Value::from(puzzles) }
+        },
+        ("POST", ["days", day]) => {
+            let day = match day.parse().ok().and_then(registry::find) {
+                Some(d) => d,
+                None => return Response::error(404, &format!("No puzzle for day {}", day)),
+            };
+            // `?preamble=5` and the like, for settings the input doesn't carry
+            let options = match Options::from_query(query) {
+                Ok(options) => options,
+                Err(message) => return Response::error(400, &message),
+            };
+            match String::from_utf8(request.body.clone()) {
+                Ok(input) => solve(day, input, options, limits, running),
+                Err(_) => Response::error(400, "Input isn't UTF-8"),
+            }
+        },
+        (_, ["puzzles"]) | (_, ["days", _]) => Response::error(405, "Method not allowed"),
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+// A puzzle that runs past its timeout can't be stopped, so its thread is left to finish on
+// its own and the answer thrown away. It keeps its slot until then, so when every slot is
+// taken new requests are turned away.
+fn solve(day: Day, input: String, options: Options, limits: Limits, running: &Running) -> Response {
+    let slot = match running.claim(limits.max_solves) {
+        Some(slot) => slot,
+        None => return Response::error(503, &format!("Already running {} puzzles, try again later", limits.max_solves)),
+    };
+    let timeout = limits.timeout.unwrap_or(day.timeout);
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _slot = slot;
+        let _ = sender.send(registry::solve(&day, &input, &options).map(|solved| {
+            json!({ "day": day.day, "title": day.title, "answers": solved.answers, "reports": solved.reports })
+        }));
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(Ok(body)) => Response { status: 200, body },
+        Ok(Err(message)) => Response::error(500, &format!("Puzzle panicked: {}", message)),
+        Err(_) => Response::error(504, &format!("Puzzle took longer than {}ms", timeout.as_millis())),
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::SocketAddr;
+
+    fn start(limits: Limits) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, limits));
+        addr
+    }
+
+    fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", method, path, body.len(), body).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_solve() {
+        let addr = start(Limits::default());
+
+        let (status, puzzles) = request(addr, "GET", "/puzzles", "");
+        assert_eq!(200, status);
+        assert_eq!(12, puzzles.as_array().unwrap().len());
+        assert_eq!("Handheld Halting", puzzles[7]["title"]);
+
+        let (status, solved) = request(addr, "POST", "/days/1", "1721\n979\n366\n299\n675\n1456\n");
+        assert_eq!(200, status);
+        assert_eq!(json!({ "a": 514579, "b": 241861950 }), solved["answers"]);
+        assert_eq!("514579", solved["reports"][0]["result"]);
+        assert_eq!("parse", solved["reports"][0]["phases"][0]["name"]);
+        assert!(solved["reports"][1]["run_us"].as_f64().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_options() {
+        let addr = start(Limits::default());
+
+        let example = registry::find(9).unwrap().example;
+        let (status, solved) = request(addr, "POST", "/days/9?preamble=5", example);
+        assert_eq!(200, status);
+        assert_eq!(json!({ "a": 127, "b": 62 }), solved["answers"]);
+        assert_eq!(400, request(addr, "POST", "/days/9?preamble=five", "").0);
+        assert_eq!(400, request(addr, "POST", "/days/9?width=5", "").0);
+    }
+
+    #[test]
+    fn test_errors() {
+        let addr = start(Limits { max_body: 16, ..Limits::default() });
+
+        assert_eq!(404, request(addr, "POST", "/days/13", "").0);
+        assert_eq!(404, request(addr, "GET", "/nothing", "").0);
+        assert_eq!(405, request(addr, "GET", "/days/1", "").0);
+        assert_eq!(413, request(addr, "POST", "/days/1", "1721\n979\n366\n299\n675\n1456\n").0);
+
+        let (status, error) = request(addr, "POST", "/days/12", "F10\nR45\n");
+        assert_eq!(500, status);
+        assert!(error["error"].as_str().unwrap().contains("45 degrees"));
+    }
+
+    fn raw(addr: SocketAddr, request: &[u8]) -> u16 {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let _ = stream.write_all(request);
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        response.split_whitespace().nth(1).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn test_header_limits() {
+        let addr = start(Limits::default());
+
+        let long_path = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE));
+        assert_eq!(400, raw(addr, long_path.as_bytes()));
+        let long_header = format!("GET /puzzles HTTP/1.1\r\nX-Long: {}\r\n\r\n", "a".repeat(MAX_LINE));
+        assert_eq!(431, raw(addr, long_header.as_bytes()));
+        let many_headers = format!("GET /puzzles HTTP/1.1\r\n{}\r\n", "X-Header: value\r\n".repeat(MAX_HEADERS / 17 + 1));
+        assert_eq!(431, raw(addr, many_headers.as_bytes()));
+        assert_eq!(200, raw(addr, b"GET /puzzles HTTP/1.1\r\nX-Header: value\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_slow_client() {
+        let addr = start(Limits { io_timeout: Duration::from_millis(50), ..Limits::default() });
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "POST /days/1 HTTP/1.1\r\nContent-Length: 10\r\n\r\n1721").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 408 "));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let addr = start(Limits { timeout: Some(Duration::from_millis(1)), ..Limits::default() });
+        let input = common::generate::generate(day_11::generate_input, 1, 60);
+
+        let (status, error) = request(addr, "POST", "/days/11", &input);
+        assert_eq!(504, status);
+        assert!(error["error"].as_str().unwrap().contains("1ms"));
+    }
+
+    #[test]
+    fn test_max_solves() {
+        let addr = start(Limits { timeout: Some(Duration::from_millis(1)), max_solves: 1, ..Limits::default() });
+        let input = common::generate::generate(day_11::generate_input, 1, 60);
+
+        // The first puzzle times out but is still running, holding the only slot
+        assert_eq!(504, request(addr, "POST", "/days/11", &input).0);
+        let (status, error) = request(addr, "POST", "/days/12", "F10\n");
+        assert_eq!(503, status);
+        assert!(error["error"].as_str().unwrap().contains("1 puzzles"));
+        assert_eq!(200, request(addr, "GET", "/puzzles", "").0);
+    }
+}